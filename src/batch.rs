@@ -0,0 +1,334 @@
+use tm_replay::*;
+use crate::json::{self, Value, obj};
+
+// Job lists are either csv with the columns
+//     slp,start_frame,duration,name,human,flags
+// or a json array of objects with those keys.
+//
+// 'start_frame' is anything FrameAddress::parse accepts, e.g. 1234, frame:1111 or 7:42.
+// Timestamps assume an 8 minute timer.
+// 'human' is one of "low", "high", "ports:H:C", "code:ABCD#123" or "name:NAME".
+// 'flags' is a '|' separated list in csv, or an array of strings in json.
+// Only slp and start_frame are required.
+
+pub struct Job {
+    pub slp: String,
+    pub start_frame: FrameAddress,
+    pub duration: usize,
+    pub name: String,
+    pub human: HumanPort,
+    pub flags: ReplayFlags,
+}
+
+pub fn parse_human(spec: &str) -> Result<HumanPort, String> {
+    let spec = spec.trim();
+    if spec.is_empty() || spec == "low" { return Ok(HumanPort::HumanLowPort) }
+    if spec == "high" { return Ok(HumanPort::HumanHighPort) }
+
+    if let Some(ports) = spec.strip_prefix("ports:") {
+        let mut ports = ports.split(':').map(|p| p.trim().parse::<usize>());
+        return match (ports.next(), ports.next(), ports.next()) {
+            (Some(Ok(human)), Some(Ok(cpu)), None) if (1..=4).contains(&human) && (1..=4).contains(&cpu) => {
+                Ok(HumanPort::Ports { human: human - 1, cpu: cpu - 1 })
+            }
+            _ => Err(format!("'{}' is not a valid port pair, expected ports:H:C with ports 1-4", spec)),
+        };
+    }
+
+    if let Some(code) = spec.strip_prefix("code:") {
+        return HumanPort::connect_code(code)
+            .ok_or_else(|| format!("'{}' is not a valid connect code", code));
+    }
+
+    if let Some(name) = spec.strip_prefix("name:") {
+        return HumanPort::name(name)
+            .ok_or_else(|| format!("'{}' is not a valid name (max 31 ASCII characters)", name));
+    }
+
+    Err(format!("'{}' is not a valid human player, expected low, high, ports:H:C, code:CODE or name:NAME", spec))
+}
+
+fn parse_flag(flag: &str) -> Result<ReplayFlags, String> {
+    match flag.trim() {
+        "" => Ok(0),
+        "skip_to_respawn" => Ok(replay_flags::SKIP_TO_RESPAWN),
+        "swap_sheik_zelda" => Ok(replay_flags::SWAP_SHEIK_ZELDA),
+        f => Err(format!("unknown flag '{}'", f)),
+    }
+}
+
+pub fn parse_jobs(path: &str, text: &str) -> Result<Vec<Job>, String> {
+    if path.to_ascii_lowercase().ends_with(".json") {
+        parse_json_jobs(text)
+    } else {
+        parse_csv_jobs(text)
+    }
+}
+
+fn parse_csv_jobs(text: &str) -> Result<Vec<Job>, String> {
+    let mut jobs = Vec::new();
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue }
+
+        let cols = line.split(',').map(str::trim).collect::<Vec<_>>();
+        if cols[0] == "slp" { continue } // header
+
+        let err = |e: String| format!("line {}: {}", line_idx+1, e);
+        let col = |i: usize| cols.get(i).copied().filter(|c| !c.is_empty());
+        let start_frame = match col(1) {
+            Some(c) => FrameAddress::parse(c, DEFAULT_TIMER_SECONDS)
+                .ok_or_else(|| err(format!("'{}' is not a valid start frame", c)))?,
+            None => return Err(err("start_frame is required".into())),
+        };
+        let duration = match col(2) {
+            Some(c) => c.parse::<usize>().map_err(|_| err(format!("'{}' is not a number", c)))?,
+            None => 360,
+        };
+
+        let mut flags = 0;
+        for flag in col(5).unwrap_or("").split('|') {
+            flags |= parse_flag(flag).map_err(err)?;
+        }
+
+        jobs.push(Job {
+            slp: cols[0].to_string(),
+            start_frame,
+            duration,
+            name: col(3).unwrap_or("new_recording").to_string(),
+            human: parse_human(col(4).unwrap_or("")).map_err(err)?,
+            flags,
+        });
+    }
+
+    Ok(jobs)
+}
+
+fn parse_json_jobs(text: &str) -> Result<Vec<Job>, String> {
+    let entries = match json::parse(text)? {
+        Value::Arr(entries) => entries,
+        _ => return Err("expected a json array of jobs".into()),
+    };
+
+    let mut jobs = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let err = |e: String| format!("job {}: {}", i+1, e);
+        let num = |key: &str| match entry.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(v) => match v.as_num() {
+                Some(n) if n >= 0.0 && n.fract() == 0.0 => Ok(Some(n as usize)),
+                _ => Err(format!("'{}' must be a non-negative integer", key)),
+            },
+        };
+        let string = |key: &str| match entry.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(v) => v.as_str().map(Some).ok_or_else(|| format!("'{}' must be a string", key)),
+        };
+
+        let start_frame = match entry.get("start_frame") {
+            None | Some(Value::Null) => return Err(err("start_frame is required".into())),
+            Some(Value::Num(_)) => FrameAddress::Index(num("start_frame").map_err(err)?.unwrap()),
+            Some(Value::Str(s)) => FrameAddress::parse(s, DEFAULT_TIMER_SECONDS)
+                .ok_or_else(|| err(format!("'{}' is not a valid start frame", s)))?,
+            Some(_) => return Err(err("'start_frame' must be a number or a string".into())),
+        };
+
+        let mut flags = 0;
+        match entry.get("flags") {
+            None | Some(Value::Null) => (),
+            Some(Value::Arr(list)) => for flag in list {
+                let flag = flag.as_str().ok_or_else(|| err("'flags' must be an array of strings".into()))?;
+                flags |= parse_flag(flag).map_err(err)?;
+            },
+            Some(_) => return Err(err("'flags' must be an array of strings".into())),
+        }
+
+        jobs.push(Job {
+            slp: string("slp").map_err(err)?.ok_or_else(|| err("slp is required".into()))?.to_string(),
+            start_frame,
+            duration: num("duration").map_err(err)?.unwrap_or(360),
+            name: string("name").map_err(err)?.unwrap_or("new_recording").to_string(),
+            human: parse_human(string("human").map_err(err)?.unwrap_or("")).map_err(err)?,
+            flags,
+        });
+    }
+
+    Ok(jobs)
+}
+
+// Appends _1, _2, ... before the extension until the path is unused.
+pub fn unique_path(dir: &std::path::Path, filename: &str) -> std::path::PathBuf {
+    let path = dir.join(filename);
+    if !path.exists() { return path }
+
+    let (stem, ext) = filename.rsplit_once('.').unwrap_or((filename, ""));
+    let mut n = 1;
+    loop {
+        let path = dir.join(format!("{}_{}.{}", stem, n, ext));
+        if !path.exists() { return path }
+        n += 1;
+    }
+}
+
+// Writes the gci to a new file in the output directory, returning its path.
+pub fn write_gci(output_dir: &std::path::Path, time: RecordingTime, gci: &[u8]) -> Result<String, String> {
+    let path = unique_path(output_dir, &dolphin_gci_filename(time));
+    std::fs::write(&path, gci)
+        .map_err(|e| format!("Could not write output file '{}': {}", path.display(), e))?;
+    Ok(path.display().to_string())
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut json = false;
+    let mut output_dir = String::from(".");
+    let mut file = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => {
+                json = true;
+                i += 1;
+            }
+            "-o" | "--output-dir" => {
+                output_dir = args.get(i+1)
+                    .ok_or_else(|| format!("Error: flag '{}' requires an argument", args[i]))?
+                    .clone();
+                i += 2;
+            }
+            "-h" | "--help" => {
+                print!("{}", crate::USAGE);
+                return Ok(());
+            }
+            f if file.is_none() && !f.starts_with('-') => {
+                file = Some(f.to_string());
+                i += 1;
+            }
+            err => return Err(format!("Error: Unknown argument '{}'", err)),
+        }
+    }
+
+    let file = file.ok_or("Error: 'batch' requires a job file")?;
+    let text = std::fs::read_to_string(&file)
+        .map_err(|e| format!("Error: Could not read file '{}': {}", file, e))?;
+    let jobs = parse_jobs(&file, &text).map_err(|e| format!("Error: '{}': {}", file, e))?;
+
+    let output_dir = std::path::Path::new(&output_dir);
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Error: Could not create directory '{}': {}", output_dir.display(), e))?;
+
+    // Jobs are often grouped by slp file, so keep the last parsed game around.
+    let mut cached: Option<(String, slp_parser::Game)> = None;
+    let mut results = Vec::with_capacity(jobs.len());
+
+    for job in jobs.iter() {
+        let result = (|| -> Result<(String, ExportReport), String> {
+            if cached.as_ref().is_none_or(|(slp, _)| *slp != job.slp) {
+                cached = None;
+                let game = slp_parser::read_game(std::path::Path::new(&job.slp))
+                    .map_err(|e| format!("Error: failed to parse slp file: {}", e))?;
+                cached = Some((job.slp.clone(), game));
+            }
+            let game = &cached.as_ref().unwrap().1;
+
+            let start_frame = job.start_frame.resolve(game).map_err(crate::error_message)?;
+            let (savestate, report) = construct_tm_replay_from_slp(game, job.human, start_frame, job.duration, &job.name, job.flags)
+                .map_err(crate::error_message)?;
+
+            let path = write_gci(output_dir, RecordingTime::from_slp(game), &savestate)?;
+            Ok((path, report))
+        })();
+
+        if !json {
+            match &result {
+                Ok((path, report)) => {
+                    println!("ok      {} @ {} -> {}", job.slp, job.start_frame, path);
+                    for note in crate::report::notes(report) {
+                        println!("          note: {}", note);
+                    }
+                }
+                Err(e) => println!("failed  {} @ {}: {}", job.slp, job.start_frame, e),
+            }
+        }
+        results.push(result);
+    }
+
+    let succeeded = results.iter().filter(|r| r.is_ok()).count();
+    let failed = results.len() - succeeded;
+
+    if json {
+        let jobs = jobs.iter().zip(results.iter()).map(|(job, result)| obj([
+            ("slp", job.slp.as_str().into()),
+            ("start_frame", job.start_frame.to_string().into()),
+            ("duration", job.duration.into()),
+            ("name", job.name.as_str().into()),
+            ("ok", result.is_ok().into()),
+            ("output", result.as_ref().ok().map(|(path, _)| path.as_str()).into()),
+            ("actual_start_frame", result.as_ref().ok().map(|(_, r)| crate::report::slippi_frame(r.frame)).into()),
+            ("actual_duration", result.as_ref().ok().map(|(_, r)| r.duration).into()),
+            ("notes", Value::Arr(result.as_ref().map_or(Vec::new(), |(_, r)| {
+                crate::report::notes(r).into_iter().map(Value::from).collect()
+            }))),
+            ("error", result.as_ref().err().map(String::as_str).into()),
+        ])).collect();
+
+        println!("{}", obj([
+            ("succeeded", succeeded.into()),
+            ("failed", failed.into()),
+            ("jobs", Value::Arr(jobs)),
+        ]));
+    } else {
+        println!();
+        println!("{} succeeded, {} failed", succeeded, failed);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn human() {
+        assert_eq!(parse_human(""), Ok(HumanPort::HumanLowPort));
+        assert_eq!(parse_human(" high "), Ok(HumanPort::HumanHighPort));
+        assert_eq!(parse_human("ports:3:1"), Ok(HumanPort::Ports { human: 2, cpu: 0 }));
+        assert_eq!(parse_human("code:abcd#123"), Ok(HumanPort::connect_code("ABCD#123").unwrap()));
+        assert_eq!(parse_human("name:Ｆox"), Ok(HumanPort::name("Fox").unwrap()));
+        for spec in ["ports:0:1", "ports:1:5", "ports:1", "ports:1:2:3", "code:ぷりん", "name:ぷりん", "mid"] {
+            assert!(parse_human(spec).is_err(), "'{}' parsed", spec);
+        }
+    }
+
+    #[test]
+    fn csv_jobs() {
+        let text = "slp,start_frame,duration,name,human,flags\n\
+                    # comment\n\
+                    a.slp,frame:1000,,combo,high,skip_to_respawn\n\
+                    b.slp,7:42\n";
+        let jobs = parse_jobs("jobs.csv", text).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].start_frame, FrameAddress::SlippiFrame(1000));
+        assert_eq!((jobs[0].duration, jobs[0].name.as_str()), (360, "combo"));
+        assert_eq!((jobs[0].human, jobs[0].flags), (HumanPort::HumanHighPort, replay_flags::SKIP_TO_RESPAWN));
+        assert_eq!(jobs[1].start_frame, FrameAddress::Timer { seconds: 462, timer_start: DEFAULT_TIMER_SECONDS });
+
+        let err = parse_jobs("jobs.csv", "a.slp,1\nb.slp,1,x\n").err().unwrap();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
+
+    #[test]
+    fn json_jobs() {
+        let text = r#"[{"slp":"a.slp","start_frame":120,"flags":["skip_to_respawn"]},{"slp":"b.slp","start_frame":"1:00","human":"ports:2:4"}]"#;
+        let jobs = parse_jobs("jobs.JSON", text).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!((jobs[0].start_frame, jobs[0].flags), (FrameAddress::Index(120), replay_flags::SKIP_TO_RESPAWN));
+        assert_eq!(jobs[1].human, HumanPort::Ports { human: 1, cpu: 3 });
+
+        assert!(parse_jobs("jobs.json", r#"[{"slp":"a.slp","start_frame":-1}]"#).is_err());
+        assert!(parse_jobs("jobs.json", r#"[{"start_frame":1}]"#).is_err());
+        assert!(parse_jobs("jobs.json", r#"{"slp":"a.slp"}"#).is_err());
+    }
+}
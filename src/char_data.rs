@@ -57,3 +57,38 @@ pub const GRAB_RELEASE_POS: &[(f32, f32)] = &[
     (-13.847497, 0.488397 ),
     (-9.848478, -0.426343 ),
 ];
+
+// Aerial side and up specials, some of which can only be used once per airtime.
+// (first state id, last state id (inclusive))
+//
+// State ids are from the special anim maps in examples/extract_fn_table.rs.
+// Where the used flags are stored is unknown, so these are only used for the export report.
+pub const AIR_SPECIALS: &[&[(u16, u16)]] = &[
+    &[(346, 346), (348, 348)], // Mario: SuperSheetAir, SuperJumpPunchAir
+    &[(350, 352), (354, 358)], // Fox: IllusionStartupAir..IllusionAirEnd, FireBirdAirStartup..FireBirdAirEnd
+    &[(351, 352), (354, 354)], // Captain Falcon: RaptorBoostAir, RaptorBoostAirHit, FalconDiveAir
+    &[(380, 380), (382, 382)], // Donkey Kong: HeadbuttAir, SpinningKongAir
+    &[(384, 384), (389, 392)], // Kirby: HammerAir, FinalCutterAirStartup..FinalCutterAirEnd
+    &[(360, 360)],             // Bowser: WhirlingFortressAir
+    &[(357, 357)],             // Link: SpinAttackAir
+    &[(358, 360)],             // Sheik: VanishAirStartup..VanishAirReappear
+    &[],                       // Ness
+    &[(357, 360), (363, 363)], // Peach: BomberAirStartup..BomberAir, ParasolAirStart
+    &[],                       // Popo
+    &[],                       // Nana
+    &[(348, 352), (356, 358)], // Pikachu: SkullBashAirStartup..SkullBashAirTakeoff, AgilityAirStartup..AgilityAirEnd
+    &[(354, 354)],             // Samus: ScrewAttackAir
+    &[],                       // Yoshi
+    &[(364, 364), (366, 368)], // Jigglypuff: PoundAir, SingAirLeft..SingAirRight
+    &[(352, 352), (356, 358)], // Mewtwo: ConfusionAir, TeleportAirStartup..TeleportAirReappear
+    &[(349, 354), (356, 356)], // Luigi: GreenMissileAirStartup..GreenMissileAirTakeoffMisfire, SuperJumpPunchAir
+    &[(358, 366), (368, 368)], // Marth: DancingBlade1Air..DancingBlade4DownAir, DolphinSlashAir
+    &[(352, 354)],             // Zelda: FaroresWindAir..FaroresWindAirReappear
+    &[(357, 357)],             // Young Link: SpinAttackAir
+    &[(346, 346), (348, 348)], // Dr. Mario: SuperSheetAir, SuperJumpPunchAir
+    &[(350, 352), (354, 358)], // Falco: IllusionStartupAir..IllusionAirEnd, FireBirdAirStartup..FireBirdAirEnd
+    &[(348, 352), (356, 358)], // Pichu: SkullBashAirStartup..SkullBashAirTakeoff, AgilityAirStartup..AgilityAirEnd
+    &[(364, 372), (374, 374)], // Mr. Game & Watch: Judgment1Air..Judgment9Air, FireAir
+    &[(351, 352), (354, 354)], // Ganondorf: GerudoDragonAir, GerudoDragonAirHit, DarkDiveAir
+    &[(358, 366), (368, 368)], // Roy: DoubleEdgeDance1Air..DoubleEdgeDance4DownAir, BlazerAir
+];
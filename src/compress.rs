@@ -73,34 +73,35 @@ pub fn lz77_compress(uncompressed_text: &[u8], uncompressed_size: u32, compresse
 }
 
 // converts from big endian
-pub fn lz77_decompress(compressed_text: &[u8], uncompressed_text: &mut [u8]) -> usize {
-    let uncompressed_size = u32::from_be_bytes(compressed_text[0..4].try_into().unwrap()) as usize;
-    let pointer_length_width = compressed_text[4];
+pub fn lz77_decompress(compressed_text: &[u8], uncompressed_text: &mut [u8]) -> Option<usize> {
+    let uncompressed_size = u32::from_be_bytes(compressed_text.get(0..4)?.try_into().unwrap()) as usize;
+    let pointer_length_width = *compressed_text.get(4)?;
+    if pointer_length_width >= 16 { return None; }
 
     let mut compressed_pointer = 5;
-    let pointer_length_mask = (1 << pointer_length_width) - 1;
+    let pointer_length_mask = (1u16 << pointer_length_width) - 1;
 
     let mut coding_pos = 0usize;
     while coding_pos < uncompressed_size {
-        let input_pointer = u16::from_be_bytes(compressed_text[compressed_pointer..][..2].try_into().unwrap());
+        let input_pointer = u16::from_be_bytes(compressed_text.get(compressed_pointer..)?.get(..2)?.try_into().unwrap());
         compressed_pointer += 2;
         let pointer_pos = input_pointer >> pointer_length_width;
         let mut pointer_length = if pointer_pos != 0 { (input_pointer & pointer_length_mask) + 1 } else { 0 };
         if pointer_pos != 0 {
-            let mut pointer_offset = coding_pos - pointer_pos as usize;
+            let mut pointer_offset = coding_pos.checked_sub(pointer_pos as usize)?;
             while pointer_length > 0 {
-                uncompressed_text[coding_pos] = uncompressed_text[pointer_offset];
+                *uncompressed_text.get_mut(coding_pos)? = uncompressed_text[pointer_offset];
                 coding_pos += 1;
                 pointer_offset += 1;
                 
                 pointer_length -= 1;
             }
         }
-        uncompressed_text[coding_pos] = compressed_text[compressed_pointer];
+        *uncompressed_text.get_mut(coding_pos)? = *compressed_text.get(compressed_pointer)?;
         compressed_pointer += 1;
 
         coding_pos += 1;
     }
 
-    return coding_pos;
+    Some(coding_pos)
 }
@@ -0,0 +1,63 @@
+use tm_replay::*;
+use crate::json::{Value, obj};
+use crate::report::{fighter_name, reason_name, slippi_frame};
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut human = HumanPort::HumanLowPort;
+    let mut json = false;
+    let mut file = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--human" => {
+                let spec = crate::parse_str(args, &mut i)?;
+                human = crate::batch::parse_human(&spec).map_err(|e| format!("Error: {}", e))?;
+            }
+            "--json" => {
+                json = true;
+                i += 1;
+            }
+            "-h" | "--help" => {
+                print!("{}", crate::USAGE);
+                return Ok(());
+            }
+            f if file.is_none() && !f.starts_with('-') => {
+                file = Some(f.to_string());
+                i += 1;
+            }
+            err => return Err(format!("Error: Unknown argument '{}'", err)),
+        }
+    }
+
+    let file = file.ok_or("Error: 'exportable' requires an slp file")?;
+    let game = slp_parser::read_game(std::path::Path::new(&file))
+        .map_err(|e| format!("Error: failed to parse slp file: {}", e))?;
+    let ranges = exportable_ranges(&game, human).map_err(crate::error_message)?;
+
+    if json {
+        println!("{}", Value::Arr(ranges.iter().map(|range| obj([
+            ("start", range.frames.start.into()),
+            ("end", range.frames.end.into()),
+            ("start_frame", slippi_frame(range.frames.start).into()),
+            ("end_frame", slippi_frame(range.frames.end).into()),
+            ("exportable", range.unexportable.is_none().into()),
+            ("fighter", range.unexportable.map(|(fighter, _)| fighter_name(fighter)).into()),
+            ("reason", range.unexportable.map(|(_, reason)| reason_name(reason)).into()),
+        ])).collect()));
+    } else {
+        for range in ranges.iter() {
+            let status = match range.unexportable {
+                None => "exportable".to_string(),
+                Some((fighter, reason)) => format!("not exportable: {} ({})", reason_name(reason), fighter_name(fighter)),
+            };
+            println!(
+                "{:>11} .. {:<11} {:>5} frames  {}",
+                slippi_frame(range.frames.start), slippi_frame(range.frames.end),
+                range.frames.len(), status,
+            );
+        }
+    }
+
+    Ok(())
+}
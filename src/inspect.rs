@@ -0,0 +1,133 @@
+use tm_replay::*;
+use crate::json::{Value, obj};
+
+/// Name of an action state without the enum wrappers, e.g. "Standard(Wait)" -> "Wait".
+pub fn state_name(state_num: u16, character: slp_parser::Character) -> String {
+    match slp_parser::ActionState::from_u16(state_num, character) {
+        Some(state) => search::action_state_name(state),
+        None => format!("Unknown({})", state_num),
+    }
+}
+
+fn hmn_mode_name(mode: u8) -> &'static str {
+    ["Off", "Record", "Playback"].get(mode as usize).copied().unwrap_or("Unknown")
+}
+
+fn cpu_mode_name(mode: u8) -> &'static str {
+    ["Off", "Control", "Record", "Playback"].get(mode as usize).copied().unwrap_or("Unknown")
+}
+
+fn slot_name(slot: u8) -> String {
+    match slot {
+        0 => "Random".to_string(),
+        n => format!("Slot {}", n),
+    }
+}
+
+fn date(time: RecordingTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        time.year, time.month, time.day,
+        time.hour, time.minute, time.second,
+    )
+}
+
+pub fn print_text(r: &ReplayInspection) {
+    let m = &r.menu_settings;
+
+    println!("Name:         {}", r.name);
+    println!("GCI name:     {}", r.gci_name);
+    println!("Version:      {}", r.version);
+    println!("Date:         {}", date(r.time));
+    println!("Stage:        {:?}", r.stage);
+    println!("Human:        {:?} (costume {})", r.hmn_character.character(), r.hmn_character.costume_idx());
+    println!("Cpu:          {:?} (costume {})", r.cpu_character.character(), r.cpu_character.costume_idx());
+    println!("Human mode:   {} ({})", hmn_mode_name(m[0]), slot_name(m[1]));
+    println!("Cpu mode:     {} ({})", cpu_mode_name(m[2]), slot_name(m[3]));
+    println!("Loop inputs:  {}", m[4] != 0);
+    println!("Auto restore: {}", m[5] != 0);
+
+    for (label, st, slots) in [("Human", &r.hmn_state, &r.hmn_slots), ("Cpu", &r.cpu_state, &r.cpu_slots)] {
+        println!();
+        println!("{} fighter:", label);
+        println!("  character:   {:?} (costume {}, {} stocks)", st.character, st.costume, st.stocks);
+        println!("  state:       {} ({}) frame {} speed {}", state_name(st.state_num, st.character), st.state_num, st.state_frame, st.state_speed);
+        println!("  direction:   {:?}", st.direction);
+        println!("  position:    {}, {}", st.position[0], st.position[1]);
+        println!("  velocity:    {}, {}", st.self_velocity[0], st.self_velocity[1]);
+        println!("  knockback:   {}, {}", st.hit_velocity[0], st.hit_velocity[1]);
+        println!("  percent:     {}", st.percent);
+        println!("  hitlag:      {}", st.hitlag_frames_left);
+        println!("  jumps used:  {}", st.jumps_used);
+        println!("  shield:      {}", st.shield_health);
+        let stale = st.stale_moves.iter()
+            .filter(|(attack, _)| *attack != 0)
+            .map(|(attack, _)| attack.to_string())
+            .collect::<Vec<_>>();
+        println!("  stale moves: [{}]", stale.join(", "));
+
+        println!("  input slots:");
+        for (i, slot) in slots.iter().enumerate() {
+            if slot.start_frame == -1 { continue }
+            println!(
+                "    {}: start frame {}, {} frames, {} with input",
+                i+1, slot.start_frame, slot.num_frames, slot.active_frames
+            );
+        }
+    }
+}
+
+fn ft_state_json(st: &FtStateInspection) -> Value {
+    obj([
+        ("character", format!("{:?}", st.character).into()),
+        ("costume", st.costume.into()),
+        ("stocks", st.stocks.into()),
+        ("state", state_name(st.state_num, st.character).into()),
+        ("state_num", st.state_num.into()),
+        ("state_frame", st.state_frame.into()),
+        ("state_speed", st.state_speed.into()),
+        ("direction", format!("{:?}", st.direction).into()),
+        ("position", st.position.into()),
+        ("self_velocity", st.self_velocity.into()),
+        ("hit_velocity", st.hit_velocity.into()),
+        ("percent", st.percent.into()),
+        ("hitlag_frames_left", st.hitlag_frames_left.into()),
+        ("jumps_used", st.jumps_used.into()),
+        ("shield_health", st.shield_health.into()),
+        ("stale_moves", Value::Arr(st.stale_moves.iter().map(|(attack, instance)| obj([
+            ("attack", (*attack).into()),
+            ("instance_id", (*instance).into()),
+        ])).collect())),
+    ])
+}
+
+fn slots_json(slots: &[InputSlotInspection]) -> Value {
+    Value::Arr(slots.iter().map(|slot| obj([
+        ("start_frame", slot.start_frame.into()),
+        ("num_frames", slot.num_frames.into()),
+        ("active_frames", slot.active_frames.into()),
+    ])).collect())
+}
+
+pub fn to_json(r: &ReplayInspection) -> Value {
+    let m = &r.menu_settings;
+    obj([
+        ("name", r.name.as_str().into()),
+        ("gci_name", r.gci_name.as_str().into()),
+        ("version", r.version.into()),
+        ("date", date(r.time).into()),
+        ("stage", format!("{:?}", r.stage).into()),
+        ("menu_settings", obj([
+            ("hmn_mode", hmn_mode_name(m[0]).into()),
+            ("hmn_slot", slot_name(m[1]).into()),
+            ("cpu_mode", cpu_mode_name(m[2]).into()),
+            ("cpu_slot", slot_name(m[3]).into()),
+            ("loop_inputs", (m[4] != 0).into()),
+            ("auto_restore", (m[5] != 0).into()),
+        ])),
+        ("hmn", ft_state_json(&r.hmn_state)),
+        ("cpu", ft_state_json(&r.cpu_state)),
+        ("hmn_slots", slots_json(&r.hmn_slots)),
+        ("cpu_slots", slots_json(&r.cpu_slots)),
+    ])
+}
@@ -0,0 +1,45 @@
+// Intangibility granted by the current action, such as rolls, dodges, techs and getups.
+//
+// This is not exported, only reported. The hurt kind is only known to work with the ledge and respawn
+// timers, which count down and clear it. Whether it holds, and is cleared, with no timer
+// during an action hasn't been checked. Moves that make single hurtboxes intangible
+// (e.g. Fox's upsmash legs) are not tracked either, as per-bone hurtbox states are not part of the savestate.
+//
+// Counters are special action states, which are not exported (see special_states.rs).
+// Star and item invincibility is not tracked either, as items are not exported.
+//
+// These windows vary slightly between characters. Most characters are close to these values.
+
+// (state id, intangible anim frames)
+pub const INTANGIBLE_STATES: &[(u16, std::ops::Range<u32>)] = &[
+    (186, 0..22 ), // DownStandU
+    (187, 0..20 ), // DownAttackU
+    (188, 0..20 ), // DownFowardU
+    (189, 0..20 ), // DownBackU
+    (194, 0..22 ), // DownStandD
+    (195, 0..20 ), // DownAttackD
+    (196, 0..20 ), // DownFowardD
+    (197, 0..20 ), // DownBackD
+    (199, 0..20 ), // Passive
+    (200, 0..20 ), // PassiveStandF
+    (201, 0..20 ), // PassiveStandB
+    (202, 0..20 ), // PassiveWall
+    (203, 0..8  ), // PassiveWallJump
+    (204, 0..20 ), // PassiveCeil
+    (233, 4..20 ), // EscapeF
+    (234, 4..20 ), // EscapeB
+    (235, 2..16 ), // Escape
+    (236, 4..30 ), // EscapeAir
+    (254, 0..55 ), // CliffClimbSlow
+    (255, 0..31 ), // CliffClimbQuick
+    (256, 0..56 ), // CliffAttackSlow
+    (257, 0..24 ), // CliffAttackQuick
+    (258, 0..60 ), // CliffEscapeSlow
+    (259, 0..30 ), // CliffEscapeQuick
+];
+
+pub fn is_intangible(state_num: u16, anim_frame: f32) -> bool {
+    INTANGIBLE_STATES.iter()
+        .find(|(id, _)| *id == state_num)
+        .is_some_and(|(_, frames)| frames.contains(&(anim_frame as u32)))
+}
@@ -0,0 +1,287 @@
+// Minimal JSON reading and writing, enough for job lists and reports.
+
+pub enum Value {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Value>),
+    Obj(Vec<(String, Value)>),
+}
+
+pub fn obj<const N: usize>(fields: [(&str, Value); N]) -> Value {
+    Value::Obj(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+impl From<bool> for Value { fn from(b: bool) -> Value { Value::Bool(b) } }
+impl From<u8> for Value { fn from(n: u8) -> Value { Value::Num(n as f64) } }
+impl From<u16> for Value { fn from(n: u16) -> Value { Value::Num(n as f64) } }
+impl From<u32> for Value { fn from(n: u32) -> Value { Value::Num(n as f64) } }
+impl From<i32> for Value { fn from(n: i32) -> Value { Value::Num(n as f64) } }
+impl From<usize> for Value { fn from(n: usize) -> Value { Value::Num(n as f64) } }
+impl From<f32> for Value { fn from(n: f32) -> Value { Value::Num(n as f64) } }
+impl From<&str> for Value { fn from(s: &str) -> Value { Value::Str(s.to_string()) } }
+impl From<String> for Value { fn from(s: String) -> Value { Value::Str(s) } }
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Value { v.map_or(Value::Null, Into::into) }
+}
+
+impl<T: Into<Value>, const N: usize> From<[T; N]> for Value {
+    fn from(v: [T; N]) -> Value { Value::Arr(v.into_iter().map(Into::into).collect()) }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Num(n) if n.is_finite() => write!(f, "{}", n),
+            Value::Num(_) => write!(f, "null"),
+            Value::Str(s) => {
+                write!(f, "\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Value::Arr(values) => {
+                write!(f, "[")?;
+                for (i, v) in values.iter().enumerate() {
+                    if i != 0 { write!(f, ",")?; }
+                    write!(f, "{}", v)?;
+                }
+                write!(f, "]")
+            }
+            Value::Obj(fields) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i != 0 { write!(f, ",")?; }
+                    write!(f, "{}:{}", Value::Str(k.clone()), v)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Obj(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_num(&self) -> Option<f64> {
+        match self {
+            Value::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse(s: &str) -> Result<Value, String> {
+    let mut p = Parser { bytes: s.as_bytes(), i: 0 };
+    let v = p.value()?;
+    p.skip_ws();
+    if p.i != p.bytes.len() { return Err(p.error("trailing characters")); }
+    Ok(v)
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    i: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> String {
+        format!("invalid JSON: {} at byte {}", msg, self.i)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.bytes.get(self.i).is_some_and(|b| b.is_ascii_whitespace()) { self.i += 1; }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.bytes.get(self.i).copied()
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), String> {
+        if self.peek() != Some(b) { return Err(self.error(&format!("expected '{}'", b as char))); }
+        self.i += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, lit: &str, v: Value) -> Result<Value, String> {
+        if !self.bytes[self.i..].starts_with(lit.as_bytes()) { return Err(self.error("unexpected character")); }
+        self.i += lit.len();
+        Ok(v)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some(b'{') => {
+                self.i += 1;
+                let mut fields = Vec::new();
+                if self.peek() == Some(b'}') {
+                    self.i += 1;
+                    return Ok(Value::Obj(fields));
+                }
+                loop {
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key, self.value()?));
+                    match self.peek() {
+                        Some(b',') => self.i += 1,
+                        Some(b'}') => { self.i += 1; return Ok(Value::Obj(fields)); }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.i += 1;
+                let mut values = Vec::new();
+                if self.peek() == Some(b']') {
+                    self.i += 1;
+                    return Ok(Value::Arr(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    match self.peek() {
+                        Some(b',') => self.i += 1,
+                        Some(b']') => { self.i += 1; return Ok(Value::Arr(values)); }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'"') => Ok(Value::Str(self.string()?)),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(_) => {
+                let start = self.i;
+                while self.bytes.get(self.i).is_some_and(|b| matches!(b, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+                    self.i += 1;
+                }
+                let num = std::str::from_utf8(&self.bytes[start..self.i]).unwrap();
+                num.parse::<f64>().map(Value::Num).map_err(|_| self.error("invalid number"))
+            }
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut s = Vec::new();
+        loop {
+            let b = *self.bytes.get(self.i).ok_or_else(|| self.error("unterminated string"))?;
+            self.i += 1;
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let e = *self.bytes.get(self.i).ok_or_else(|| self.error("unterminated string"))?;
+                    self.i += 1;
+                    let c = match e {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut n = self.hex_escape()?;
+                            // characters outside the BMP are escaped as a surrogate pair
+                            if (0xD800..0xDC00).contains(&n) && self.bytes.get(self.i..self.i+2) == Some(&b"\\u"[..]) {
+                                let i = self.i;
+                                self.i += 2;
+                                let low = self.hex_escape()?;
+                                if (0xDC00..0xE000).contains(&low) {
+                                    n = 0x10000 + ((n - 0xD800) << 10) + (low - 0xDC00);
+                                } else {
+                                    self.i = i;
+                                }
+                            }
+                            char::from_u32(n).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buf = [0u8; 4];
+                    s.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                b => s.push(b),
+            }
+        }
+        String::from_utf8(s).map_err(|_| self.error("invalid UTF-8"))
+    }
+
+    /// The 4 hex digits after a `\u`.
+    fn hex_escape(&mut self) -> Result<u32, String> {
+        let hex = self.bytes.get(self.i..self.i+4).ok_or_else(|| self.error("invalid escape"))?;
+        let hex = std::str::from_utf8(hex).map_err(|_| self.error("invalid escape"))?;
+        let n = u32::from_str_radix(hex, 16).map_err(|_| self.error("invalid escape"))?;
+        self.i += 4;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_non_finite() {
+        assert_eq!(obj([("x", f32::NAN.into()), ("y", Option::<u8>::None.into())]).to_string(), r#"{"x":null,"y":null}"#);
+        assert_eq!(Value::from([1u8, 2]).to_string(), "[1,2]");
+    }
+
+    #[test]
+    fn parse_roundtrip() {
+        let s = r#"{"a":[1,-2.5,1e3],"b":true,"c":null,"d":{"e":"f"},"g":[]}"#;
+        assert_eq!(parse(s).unwrap().to_string(), r#"{"a":[1,-2.5,1000],"b":true,"c":null,"d":{"e":"f"},"g":[]}"#);
+        assert_eq!(parse(" { \"a\" : [ 1 , 2 ] } ").unwrap().to_string(), r#"{"a":[1,2]}"#);
+    }
+
+    #[test]
+    fn parse_escapes() {
+        let v = parse(r#""a\"b\\c\/d\n\u00e9\u0001""#).unwrap();
+        assert_eq!(v.as_str(), Some("a\"b\\c/d\né\u{1}"));
+        assert_eq!(v.to_string(), r#""a\"b\\c/d\né\u0001""#);
+        assert_eq!(parse("\"ぷりん\"").unwrap().as_str(), Some("ぷりん"));
+        assert_eq!(parse(r#""\uD83D\uDE00""#).unwrap().as_str(), Some("\u{1F600}"));
+        assert_eq!(parse(r#""\uD83Dx\uDE00""#).unwrap().as_str(), Some("\u{FFFD}x\u{FFFD}"));
+        assert_eq!(parse(r#""\uD83D\n""#).unwrap().as_str(), Some("\u{FFFD}\n"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for s in ["", "{", "[1,]", "{\"a\" 1}", "\"abc", "\"\\x\"", "tru", "1 2", "-"] {
+            assert!(parse(s).is_err(), "'{}' parsed", s);
+        }
+    }
+
+    #[test]
+    fn get() {
+        let v = parse(r#"{"path":"a.slp","frame":120}"#).unwrap();
+        assert_eq!(v.get("path").and_then(Value::as_str), Some("a.slp"));
+        assert_eq!(v.get("frame").and_then(Value::as_num), Some(120.0));
+        assert!(v.get("missing").is_none());
+        assert!(v.get("frame").unwrap().as_str().is_none());
+    }
+}
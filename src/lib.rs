@@ -3,6 +3,14 @@ mod compress;
 mod autocancel;
 mod hitboxes;
 mod char_data;
+mod stage_data;
+mod special_states;
+mod resources;
+mod intangibility;
+pub mod search;
+pub mod markers;
+pub mod partial;
+pub mod punishes;
 
 pub const MIN_VERSION_MAJOR: u8 = 3;
 pub const MIN_VERSION_MINOR: u8 = 16;
@@ -64,12 +72,33 @@ pub struct RecordingTime {
 }
 
 impl RecordingTime {
+    /// The time the slp game started. Exports from this game use this time.
+    pub fn from_slp(game: &slp_parser::Game) -> RecordingTime {
+        let time = game.info.start_time.fields();
+        RecordingTime {
+            year: time.year,
+            month: time.month,
+            day: time.day,
+            hour: time.hour,
+            minute: time.minute,
+            second: time.second,
+        }
+    }
+
     pub fn today_approx() -> RecordingTime {
-        use std::time::SystemTime;
+        RecordingTime {
+            hour: 0,
+            minute: 0,
+            second: 0,
+            ..RecordingTime::from_system_time(std::time::SystemTime::now())
+        }
+    }
 
-        let seconds = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
+    /// In UTC, like the start time Slippi writes to slp files.
+    pub fn from_system_time(time: std::time::SystemTime) -> RecordingTime {
+        let seconds = time
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
             .as_secs();
 
         let mut days = seconds / (60 * 60 * 24);
@@ -113,13 +142,15 @@ impl RecordingTime {
 
         days += 1; // one-index the day
 
+        let second_of_day = seconds % (60 * 60 * 24);
+
         RecordingTime {
             year,
             month,
             day: days as u8,
-            hour: 0,
-            minute: 0,
-            second: 0,
+            hour: (second_of_day / (60 * 60)) as u8,
+            minute: (second_of_day / 60 % 60) as u8,
+            second: (second_of_day % 60) as u8,
         }
     }
 }
@@ -141,7 +172,7 @@ fn vector_to_arr(v: slp_parser::Vector) -> [f32; 2] { [v.x, v.y] }
 pub fn valid_filename_char(c: char) -> bool {
     match c {
         c if c.is_ascii_alphanumeric() => true,
-        ' ' | '-' | ',' | '.' | '!' | '%' | '^' | '(' | ')' => true,
+        ' ' | '-' | ',' | '.' | ':' | '!' | '%' | '^' | '(' | ')' => true,
         _ => false,
     }
 }
@@ -168,10 +199,12 @@ pub struct RecordingState {
     pub start_frame: i32,
     pub stage: slp_parser::Stage,
     pub hmn_state: CharacterState,
-    /// The stale moves and costume fields will be ignored.
+    /// The costume field will be ignored - followers always share the leader's costume.
+    /// Stale moves are ignored too, as there is one queue per player. Put Nana's hits in the leader's queue.
     pub hmn_follower_state: Option<CharacterState>,
     pub cpu_state: CharacterState,
-    /// The stale moves and costume fields will be ignored.
+    /// The costume field will be ignored - followers always share the leader's costume.
+    /// Stale moves are ignored too, as there is one queue per player. Put Nana's hits in the leader's queue.
     pub cpu_follower_state: Option<CharacterState>,
 }
 
@@ -259,6 +292,40 @@ impl SmashAttack {
     };
 }
 
+/// Ice Climbers follower (Nana) bookkeeping.
+///
+/// Nana replays Popo's inputs a few frames late.
+/// If she can't act on an input when Popo does, she desyncs and acts on her own.
+///
+/// This is only used for the export report.
+/// Nana's queue of Popo's inputs and her AI state are not written, as their layout in the savestate is unknown.
+/// Playback uses the template's, so Nana starts with an empty queue.
+#[derive(Copy, Clone, Debug)]
+pub struct FollowerState {
+    /// Number of frames Nana lags behind Popo.
+    pub delay: u8,
+    /// Set if Nana is not copying Popo's inputs.
+    pub desynced: bool,
+}
+
+impl FollowerState {
+    pub const SYNCED: FollowerState = FollowerState {
+        delay: 0,
+        desynced: false,
+    };
+}
+
+/// Grab linkage between the two fighters.
+///
+/// The partner is always the other player's leader fighter.
+#[derive(Copy, Clone, Debug)]
+pub struct GrabState {
+    /// Set for the grabbed fighter, unset for the grabber.
+    pub grabbed: bool,
+    /// Frames until the grabbed fighter breaks out. Only used by the grabbed fighter.
+    pub timer: f32,
+}
+
 #[derive(Clone, Debug)]
 /// Initial state for a character.
 ///
@@ -281,8 +348,15 @@ pub struct CharacterState {
     pub x_rotn_rot: [f32; 4],
     pub direction: slp_parser::Direction,
     pub percent: f32,
+    /// Stocks remaining. Already decremented while respawning.
+    pub stocks: u8,
     pub last_ground_idx: u32,
     pub jumps_remaining: u8,
+    /// Number of wall jumps since last touching the ground.
+    pub walljumps_used: u8,
+    /// Set if an aerial side or up special, or an airdodge, was used since last touching the ground.
+    /// The flags limiting these per airtime are not written, so this is only used for the export report.
+    pub airtime_specials_used: bool,
     /// Zero if n/a. See https://docs.google.com/spreadsheets/d/1spibzWaitiA22s7db1AEw1hqQXzPDNFZHYjc4czv2dc
     pub stale_moves: [slp_parser::StaleMove; 10],
     pub anim_velocity: [f32; 3],
@@ -298,11 +372,26 @@ pub struct CharacterState {
     
     pub intang_ledge: u32,
     pub intang_respawn: u32,
+    /// Intangible from the current action, such as a roll, tech or ledge getup.
+    /// Only used for the export report, see intangibility.rs.
+    pub intang_action: bool,
+
+    /// Between 0 and 60, where 60 is a full shield.
+    pub shield_health: f32,
+    /// Trigger amount held for shielding, between 0 and 1. Set to 1 when the trigger is held digitally.
+    /// Controls shield size and light shield pushback.
+    pub shield_light: f32,
 
     /// Generic character state variables, used for special actions.
     ///
+    /// ## 0..4
+    /// - Peach: 1 if float hasn't been used this airtime
+    ///
     /// ## 4..8
     /// - frames of float left
+    ///
+    /// Stored charges, needles, oil and Kirby's copied ability are somewhere in here too,
+    /// but where is unknown, so they are not written (see resources.rs).
     pub char_fighter_var: [u8; 208],
 
     /// Generic character state variables, used for most actions.
@@ -323,6 +412,14 @@ pub struct CharacterState {
     pub state_flags: [u8; 5],
 
     pub hitlag_frames_left: f32,
+
+    /// Knockback of the last hit while in hitstun, otherwise zero.
+    /// Decides tumble, the hitstun length and the launch speed.
+    pub knockback: f32,
+
+    /// Set if in hitstun or hitlag, and last hit by the opponent's port.
+    /// The opponent is written as the damage source.
+    pub hit_by_opponent: bool,
     pub stick: [f32; 2],
     pub cstick: [f32; 2],
     pub prev_stick: [f32; 2],
@@ -332,6 +429,16 @@ pub struct CharacterState {
     pub last_lstick_x_direction: slp_parser::Direction,
     pub input_timers: InputTimers,
     pub smash_attack: SmashAttack,
+
+    /// Set while grabbing or grabbed by the opponent. Includes throws.
+    pub grab: Option<GrabState>,
+
+    /// Only used for Nana. Must be None for all other characters.
+    pub follower: Option<FollowerState>,
+
+    /// Set if the fighter has a stored charge, needles, oil or a copied ability.
+    /// These are not written, so this is only used for the export report.
+    pub stored_resources: bool,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -669,7 +776,10 @@ impl Default for CharacterState {
             state: slp_parser::ActionState::Standard(slp_parser::StandardActionState::Wait),
             state_frame: 0.0,
             jumps_remaining: 0,
+            walljumps_used: 0,
+            airtime_specials_used: false,
             percent: 0.0,
+            stocks: 4,
             stale_moves: [slp_parser::StaleMove::NULL; 10],
             anim_velocity: [0.0; 3],
             self_velocity: [0.0; 3],
@@ -678,6 +788,8 @@ impl Default for CharacterState {
             frames_since_hit: -1,
             offscreen_damage_timer: 0,
             hitlag_frames_left: 0.0,
+            knockback: 0.0,
+            hit_by_opponent: false,
             char_fighter_var: [0u8; 208],
             char_state_var: [0u8; 72],
             subaction_flags: [0u8; 16],
@@ -698,6 +810,12 @@ impl Default for CharacterState {
             smash_attack: SmashAttack::NONE,
             intang_ledge: 0,
             intang_respawn: 0,
+            intang_action: false,
+            shield_health: 60.0,
+            shield_light: 0.0,
+            grab: None,
+            follower: None,
+            stored_resources: false,
         }
     }
 }
@@ -771,6 +889,146 @@ impl Input {
     }
 }
 
+fn input_from_frame(f: &slp_parser::Frame) -> Input {
+    Input {
+        button_flags: translate_buttons_from_slp(f.buttons_mask),
+        stick_x: (f.left_stick_coords.x * 80.0) as i8,
+        stick_y: (f.left_stick_coords.y * 80.0) as i8,
+        cstick_x: (f.right_stick_coords.x * 80.0) as i8,
+        cstick_y: (f.right_stick_coords.y * 80.0) as i8,
+        trigger: (f.analog_trigger_value * 140.0) as u8,
+    }
+}
+
+fn is_grabbing(state: slp_parser::ActionState) -> bool {
+    use slp_parser::{ActionState, StandardActionState::*};
+    matches!(state, ActionState::Standard(
+        CatchPull | CatchDashPull | CatchWait | CatchAttack
+            | ThrowF | ThrowB | ThrowHi | ThrowLw
+    ))
+}
+
+fn is_grabbed(state: slp_parser::ActionState) -> bool {
+    use slp_parser::{ActionState, StandardActionState::*};
+    matches!(state, ActionState::Standard(
+        CapturePulledHi | CaptureWaitHi | CaptureDamageHi 
+            | CapturePulledLw | CaptureWaitLw | CaptureDamageLw
+            | ThrownF | ThrownB | ThrownHi | ThrownLw | ThrownLwWomen
+    ))
+}
+
+fn is_dead(state: slp_parser::ActionState) -> bool {
+    use slp_parser::{ActionState, StandardActionState::*};
+    matches!(state, ActionState::Standard(
+        DeadDown | DeadLeft | DeadRight | DeadUp | DeadUpStar | DeadUpStarIce | DeadUpFall
+            | DeadUpFallHitCamera | DeadUpFallHitCameraFlat | DeadUpFallIce | DeadUpFallHitCameraIce
+    ))
+}
+
+fn in_knockback(state: slp_parser::ActionState) -> bool {
+    use slp_parser::{ActionState, StandardActionState::*};
+    matches!(state, ActionState::Standard(
+        DamageHi1 | DamageHi2 | DamageHi3 | DamageN1 | DamageN2 | DamageN3
+            | DamageLw1 | DamageLw2 | DamageLw3 | DamageAir1 | DamageAir2 | DamageAir3
+            | DamageFlyHi | DamageFlyN | DamageFlyLw | DamageFlyTop | DamageFlyRoll
+    ))
+}
+
+/// Why a frame can't be exported from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Unexportable {
+    /// The entry animation at the start of the game. Exports start after it instead.
+    Entry,
+    /// KOed and past the blast zone. Respawning is fine.
+    Dead,
+    /// An attack hitbox is active. Hitboxes are not exported.
+    /// Hitlag on the last active frame is fine, as the hitbox has already connected.
+    AttackHitbox,
+    /// Grabbed by a command grab, breaking out of a grab, or a follower is in a grab.
+    /// Only standard grabs between the two leaders are exported.
+    Grab,
+    /// Buried, sung to sleep, held in a barrel, carried by DK, in shieldstun, shield broken and so on.
+    UnsupportedState,
+    /// A special action state that can't be reconstructed, such as Kirby's swallow or controlling PK Thunder.
+    SpecialState,
+    /// In hitlag before the knockback velocity is recorded.
+    /// The velocity after hitlag already has DI applied, so it can't be used in its place.
+    PendingKnockback,
+}
+
+/// Why construct_tm_replay_from_slp would search back from this frame, if it would.
+/// The entry animation is checked separately, as exports skip forward past it instead.
+fn frame_unexportable(f: &slp_parser::Frame) -> Option<Unexportable> {
+    use slp_parser::{ActionState, StandardActionState::*};
+
+    if let special_states::Support::Unsupported = special_states::support(f.character, f.state.as_u16()) {
+        return Some(Unexportable::SpecialState);
+    }
+
+    // grab linkage is only written between the two leaders
+    if f.is_follower && (is_grabbing(f.state) || is_grabbed(f.state)) { return Some(Unexportable::Grab) }
+
+    // Grab releases are not written - the release position and momentum come from the grab struct.
+    if matches!(f.state, ActionState::Standard(
+        CatchCut | CaptureCut | CaptureJump | CaptureNeck | CaptureFoot
+            | ThrownFF | ThrownFB | ThrownFHi | ThrownFLw
+            | CaptureCaptain | CaptureYoshi | YoshiEgg | CaptureKoopa
+            | CaptureDamageKoopa | CaptureWaitKoopa | ThrownKoopaF | ThrownKoopaB
+            | CaptureKoopaAir | CaptureDamageKoopaAir | CaptureWaitKoopaAir | ThrownKoopaAirF | ThrownKoopaAirB
+            | CaptureKirby | CaptureWaitKirby | ThrownKirbyStar | ThrownCopyStar | ThrownKirby
+    )) {
+        return Some(Unexportable::Grab);
+    }
+
+    if matches!(f.state, ActionState::Standard(
+        ShoulderedWait | ShoulderedWalkSlow | ShoulderedWalkMiddle | ShoulderedWalkFast | ShoulderedTurn
+            | BarrelWait | Bury | BuryWait | BuryJump
+            | DamageSong | DamageSongWait | DamageSongRv | DamageBind
+            | GuardSetOff | ShieldBreakFly | ShieldBreakFall | ShieldBreakDownU | ShieldBreakDownD
+            | ShieldBreakStandU | ShieldBreakStandD | FuraFura
+    )) {
+        return Some(Unexportable::UnsupportedState);
+    }
+
+    if in_knockback(f.state) && f.hitlag_frames != 0.0 && f.hit_velocity.x == 0.0 && f.hit_velocity.y == 0.0 {
+        return Some(Unexportable::PendingKnockback);
+    }
+
+    // The fighter is already past the blast zone, so they would just be KOed again.
+    // Respawning (Rebirth, RebirthWait) is fine - the platform timer is the first char state var.
+    if is_dead(f.state) { return Some(Unexportable::Dead) }
+    
+    let state_num = f.state.as_u16() as usize;
+    if hitboxes::ATTACK_RANGE_START <= state_num && state_num < hitboxes::ATTACK_RANGE_END {
+        let hitbox_range = &hitboxes::ATTACK_HITBOXES[f.character as usize][state_num - hitboxes::ATTACK_RANGE_START];
+        // Hitboxes are not exported.
+        // During hitlag on the last active frame the hitbox has already connected, so dropping it is fine.
+        // Earlier in the window, later hits of multi-hit moves would be lost.
+        let anim_frame = f.anim_frame as u32;
+        let last_active_frame = anim_frame + 1 >= hitbox_range.end;
+        if hitbox_range.contains(&anim_frame) && !(f.hitlag_frames != 0.0 && last_active_frame) {
+            return Some(Unexportable::AttackHitbox);
+        }
+    } 
+    
+    None
+}
+
+/// Whether the fighter's state on this frame can be written to a savestate.
+fn good_frame(f: &slp_parser::Frame) -> bool {
+    frame_unexportable(f).is_none()
+}
+
+/// Why a recording can't start on this frame because of this fighter, if it can't.
+fn fighter_unexportable(f: &slp_parser::Frame) -> Option<Unexportable> {
+    use slp_parser::{ActionState, StandardActionState::*};
+
+    if matches!(f.state, ActionState::Standard(Entry | EntryStart | EntryEnd)) {
+        return Some(Unexportable::Entry);
+    }
+    frame_unexportable(f)
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct InputRecordings<'a> {
     /// Each slot is truncated to 3600 frames.
@@ -790,6 +1048,7 @@ pub enum ReplayCreationError {
     FilenameNotASCII,
     SpecialActionState,
     NoGoodExportFrame,
+    PlayerNotFound,
     ZeldaOnCpu,
 }
 
@@ -800,6 +1059,10 @@ const REC_SLOT_SIZE: usize = 4 + 4 + REC_LENGTH*6;
 
 static DEFAULT_SAVESTATE_AND_MATCHINIT: &'static [u8] = include_bytes!("savestate_matchinit.raw");
 const SAVESTATE_SIZE: usize = 54616;
+const FT_STATE_SIZE: usize = 9016;
+// The savestate is a header, the event data and 6 FtStates and nothing else.
+// There is no room for the global camera or the rng seed.
+const _: () = assert!(SAVESTATE_SIZE == 8 + EVENT_DATASIZE + 6*FT_STATE_SIZE);
 const MATCHINIT_SIZE: usize = 312;
 const RECORDING_SIZE: usize = 314224;
 
@@ -1069,6 +1332,7 @@ pub fn read_replay_buffer(gci_file: &mut [u8]) -> Option<Vec<u8>> {
     let start = 0x1EB0;
     if gci_file.len() < start + 400 { return None; }
     let block_count = u16::from_be_bytes(gci_file[0x38..0x3A].try_into().unwrap()) as usize;
+    if block_count == 0 { return None; }
     let decoded_len = 400 - 32 + (block_count-1)*(BLOCK_SIZE - 32);
     let mut decoded = Vec::with_capacity(decoded_len);
 
@@ -1209,13 +1473,13 @@ pub fn construct_tm_replay(
         return Err(ReplayCreationError::ZeldaOnCpu) 
     }
 
-    //if let slp_parser::ActionState::Special(_) = state.hmn_state.state {
-    //    return Err(ReplayCreationError::SpecialActionState)
-    //}
-
-    //if let slp_parser::ActionState::Special(_) = state.cpu_state.state {
-    //    return Err(ReplayCreationError::SpecialActionState)
-    //}
+    let fighters = [Some(&state.hmn_state), state.hmn_follower_state.as_ref(), Some(&state.cpu_state), state.cpu_follower_state.as_ref()];
+    for st in fighters.into_iter().flatten() {
+        let support = special_states::support(st.character.character(), st.state.as_u16());
+        if let special_states::Support::Unsupported = support {
+            return Err(ReplayCreationError::SpecialActionState)
+        }
+    }
 
     // buffer created by unclepunch's tm code
     let mut bytes = Vec::with_capacity(8192 * 8);
@@ -1250,14 +1514,21 @@ pub fn construct_tm_replay(
 
     // write FtState values
 
-    fn write_ft_state(ft_state: &mut [u8], st: &CharacterState, follower: Option<&CharacterState>) {
+    fn write_ft_state(
+        ft_state: &mut [u8],
+        st: &CharacterState,
+        follower: Option<&CharacterState>,
+        ply: u32,
+    ) {
         let ft_savestate_data_size = 4396;
         let playerblock_offset = ft_savestate_data_size*2;
         let stale_offset = 8972;
 
-        write_ft_save_state_data(ft_state, st);
+        let opponent = 1 - ply;
+
+        write_ft_save_state_data(ft_state, st, opponent);
         if let Some(follower_st) = follower { 
-            write_ft_save_state_data(&mut ft_state[ft_savestate_data_size..], follower_st);
+            write_ft_save_state_data(&mut ft_state[ft_savestate_data_size..], follower_st, opponent);
         }
 
         // stale moves ------------------------------------
@@ -1281,9 +1552,14 @@ pub fn construct_tm_replay(
         let costume = st.character.costume_idx();
         ft_state[playerblock_offset..][4..8].copy_from_slice(&(character as u32).to_be_bytes());
         ft_state[playerblock_offset..][68] = costume;
+        ft_state[playerblock_offset..][0x8E] = st.stocks; // stocks remaining, as in the static player block
     }
     
-    fn write_ft_save_state_data(ft_state: &mut [u8], st: &CharacterState) {
+    fn write_ft_save_state_data(
+        ft_state: &mut [u8],
+        st: &CharacterState,
+        opponent: u32,
+    ) {
         // nested struct offsets
         let phys_offset = 40;
         let input_offset = 568;
@@ -1298,6 +1574,7 @@ pub fn construct_tm_replay(
         let jump_offset = 4048;
         let smash_offset = 4052;
         let hurt_offset = 4092;
+        let shield_offset = 4108; // The template has 60.0 here for both fighters, a full shield
 
         // state, direction, anim frame, anim speed, anim blend
         let state_offset = 4;
@@ -1392,6 +1669,7 @@ pub fn construct_tm_replay(
         let percent_bytes = (st.percent*0.5).to_be_bytes(); // percent is stored halved for some reason???
         ft_state[dmg_offset..][4..8].copy_from_slice(&percent_bytes); // percent
         ft_state[dmg_offset..][12..16].copy_from_slice(&percent_bytes); // temp percent???
+        ft_state[dmg_offset..][0x24..0x28].copy_from_slice(&st.knockback.to_be_bytes()); // knockback magnitude, estimated
         ft_state[dmg_offset..][0x80..0x84].copy_from_slice(&st.frames_since_hit.to_be_bytes()); // frames in knockback
         ft_state[dmg_offset..][0xE4..0xE8].copy_from_slice(&st.offscreen_damage_timer.to_be_bytes());
         
@@ -1449,6 +1727,11 @@ pub fn construct_tm_replay(
         ft_state[camera_box_offset..][32..36].copy_from_slice(&st.position[1].to_be_bytes());
         ft_state[camera_box_offset..][36..40].copy_from_slice(&st.position[2].to_be_bytes());
 
+        // The bounds after the bone pos are per-character extents relative to the fighter.
+        // The template has the same values for both fighters at different positions,
+        // so they are left as the template's.
+        // The match camera itself is not part of the savestate (see SAVESTATE_SIZE).
+
         // hitlag & hitstun handling -----------------------------
 
         if st.hitlag_frames_left > 0.0 {
@@ -1456,6 +1739,14 @@ pub fn construct_tm_replay(
             ft_state[flags_offset..][9] = 4;  // hitstop flag
         }
 
+        // The pending knockback is in hit_velocity and is applied once hitlag ends.
+
+        if st.hit_by_opponent {
+            // The damage source is a player index, not a pointer.
+            // The template has 6 here, which is no source.
+            ft_state[dmg_offset..][0x98..0x9C].copy_from_slice(&opponent.to_be_bytes()); // damage source
+        }
+
         // flags ----------------------------------------------
 
         if matches!(
@@ -1467,7 +1758,7 @@ pub fn construct_tm_replay(
         }
 
         ft_state[flags_offset..][8] = st.state_flags[0];
-        ft_state[flags_offset..][10] = st.state_flags[1];
+        ft_state[flags_offset..][10] = st.state_flags[1]; // includes fastfall
         ft_state[flags_offset..][11] = st.state_flags[2];
         ft_state[flags_offset..][12] = st.state_flags[3];
         ft_state[flags_offset..][15] = st.state_flags[4];
@@ -1513,6 +1804,7 @@ pub fn construct_tm_replay(
         }
 
         ft_state[char_fighter_var_offset..][0..208].copy_from_slice(&st.char_fighter_var);
+
         ft_state[char_state_var_offset..][0..72].copy_from_slice(&st.char_state_var);
         ft_state[subaction_flags_offset..][0..16].copy_from_slice(&st.subaction_flags);
 
@@ -1522,10 +1814,27 @@ pub fn construct_tm_replay(
         let (grab_release_x, grab_release_y) = char_data::GRAB_RELEASE_POS[internal_kind];
         ft_state[grab_offset..][0x28..][..4].copy_from_slice(&grab_release_x.to_be_bytes());
         ft_state[grab_offset..][0x2C..][..4].copy_from_slice(&grab_release_y.to_be_bytes());
+
+        // TM stores the grab_attacker and grab_victim pointers as the index of the fighter's FtState.
+        // The template has -1 in both, which is what TM writes for a null pointer.
+        // An FtState holds both a leader and its follower, so followers can't be referred to,
+        // which is why grabs involving a follower are not exported (see frame_unexportable).
+        // Left untouched outside of grabs.
+        if let Some(grab) = st.grab {
+            let (attacker, victim) = if grab.grabbed {
+                (opponent as i32, -1i32)
+            } else {
+                (-1i32, opponent as i32)
+            };
+            ft_state[grab_offset..][0x00..][..4].copy_from_slice(&grab.timer.to_be_bytes());
+            ft_state[grab_offset..][0x0C..][..4].copy_from_slice(&attacker.to_be_bytes());
+            ft_state[grab_offset..][0x10..][..4].copy_from_slice(&victim.to_be_bytes());
+        }
         
         // struct jump ----------------------------------------
 
         ft_state[jump_offset..][0] = jump_count(st.character.character())- st.jumps_remaining;
+        ft_state[jump_offset..][1] = st.walljumps_used; // directly follows jumps used in the fighter struct
         
         // struct smash ----------------------------------------
         
@@ -1538,6 +1847,7 @@ pub fn construct_tm_replay(
         
         // struct hurt ----------------------------------------
         
+        // Intangibility from the current action is not written (see intangibility.rs).
         let kind = if st.intang_ledge != 0 {
             2u32
         } else if st.intang_respawn != 0 {
@@ -1549,6 +1859,13 @@ pub fn construct_tm_replay(
         ft_state[hurt_offset..][4..][..4].copy_from_slice(&kind.to_be_bytes());
         ft_state[hurt_offset..][8..][..4].copy_from_slice(&st.intang_ledge.to_be_bytes());
         ft_state[hurt_offset..][12..][..4].copy_from_slice(&st.intang_respawn.to_be_bytes());
+
+        // struct shield --------------------------------------
+
+        // Shieldstun and shield break are not exported (see frame_unexportable),
+        // so only the shield itself needs writing.
+        ft_state[shield_offset..][0..][..4].copy_from_slice(&st.shield_health.to_be_bytes());
+        ft_state[shield_offset..][4..][..4].copy_from_slice(&st.shield_light.to_be_bytes());
         
         // callbacks (struct cb) ------------------------------
 
@@ -1570,16 +1887,18 @@ pub fn construct_tm_replay(
 
     let st_offset = 312; // savestate offset - skip MatchInit in RecordingSave
     let ft_state_offset = 8+EVENT_DATASIZE; // FtState array offset - fields in Savestate;
-    let ft_state_size = 9016;
+    let ft_state_size = FT_STATE_SIZE;
     write_ft_state(
         &mut recording_save[st_offset+ft_state_offset..][..ft_state_size],
         &state.hmn_state,
         state.hmn_follower_state.as_ref(),
+        0,
     );
     write_ft_state(
         &mut recording_save[st_offset+ft_state_offset+ft_state_size..][..ft_state_size],
         &state.cpu_state,
         state.cpu_follower_state.as_ref(),
+        1,
     );
 
     // write inputs
@@ -1656,28 +1975,370 @@ pub fn construct_tm_replay(
 pub enum HumanPort {
     HumanLowPort,
     HumanHighPort,
+    /// Any two ports, zero indexed. Used for doubles and free-for-all games.
+    /// All other fighters are omitted.
+    Ports { human: usize, cpu: usize },
+    /// Human chosen by Slippi connect code, Shift-JIS encoded as in the slp. Only for 1v1 games.
+    /// See `HumanPort::connect_code`.
+    ConnectCode([u8; 10]),
+    /// Human chosen by in-game name, as ASCII. Only for 1v1 games.
+    /// Fullwidth characters in the slp's Shift-JIS names are matched as their ASCII forms.
+    /// See `HumanPort::name`.
+    Name([u8; 31]),
+}
+
+impl HumanPort {
+    /// Returns None if the code is not ASCII or is too long.
+    pub fn connect_code(code: &str) -> Option<HumanPort> {
+        let mut bytes = [0u8; 10];
+        let mut len = 0;
+        for c in code.chars() {
+            let encoded: &[u8] = match c {
+                '#' => &[0x81, 0x94], // fullwidth '#' in Shift-JIS
+                c if c.is_ascii() => &[c.to_ascii_uppercase() as u8],
+                _ => return None,
+            };
+            if len + encoded.len() > bytes.len() { return None; }
+            bytes[len..][..encoded.len()].copy_from_slice(encoded);
+            len += encoded.len();
+        }
+        Some(HumanPort::ConnectCode(bytes))
+    }
+
+    /// Fullwidth characters are stored as their ASCII forms.
+    /// Returns None if the name has other non-ASCII characters or is longer than 31 characters.
+    pub fn name(name: &str) -> Option<HumanPort> {
+        let mut bytes = [0u8; 31];
+        let mut len = 0;
+        for c in name.chars() {
+            let c = match c {
+                '\u{3000}' => ' ',
+                '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFF01 + 0x21).unwrap(),
+                c if c.is_ascii() => c,
+                _ => return None,
+            };
+            if len == bytes.len() { return None; }
+            bytes[len] = c as u8;
+            len += 1;
+        }
+        Some(HumanPort::Name(bytes))
+    }
+}
+
+// Shift-JIS fullwidth characters that have an ASCII form.
+const SHIFT_JIS_FULLWIDTH: &[(u8, u8, u8)] = &[
+    (0x81, 0x40, b' '),
+    (0x81, 0x44, b'.'),
+    (0x81, 0x46, b':'),
+    (0x81, 0x47, b';'),
+    (0x81, 0x48, b'?'),
+    (0x81, 0x49, b'!'),
+    (0x81, 0x51, b'_'),
+    (0x81, 0x5E, b'/'),
+    (0x81, 0x69, b'('),
+    (0x81, 0x6A, b')'),
+    (0x81, 0x7B, b'+'),
+    (0x81, 0x7C, b'-'),
+    (0x81, 0x81, b'='),
+    (0x81, 0x90, b'$'),
+    (0x81, 0x93, b'%'),
+    (0x81, 0x94, b'#'),
+    (0x81, 0x95, b'&'),
+    (0x81, 0x96, b'*'),
+    (0x81, 0x97, b'@'),
+];
+
+/// Converts a Shift-JIS name from the slp to ASCII where it can, for comparing against `HumanPort::Name`.
+/// Other characters are kept as they are. Stops at the first null byte.
+fn normalize_slp_name(name: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(name.len());
+    let mut i = 0;
+    while i < name.len() && name[i] != 0 {
+        let (a, b) = (name[i], name.get(i+1).copied().unwrap_or(0));
+        let ascii = match (a, b) {
+            (0x82, 0x4F..=0x58) => Some(b - 0x4F + b'0'),
+            (0x82, 0x60..=0x79) => Some(b - 0x60 + b'A'),
+            (0x82, 0x81..=0x9A) => Some(b - 0x81 + b'a'),
+            _ => SHIFT_JIS_FULLWIDTH.iter().find(|(x, y, _)| *x == a && *y == b).map(|(_, _, c)| *c),
+        };
+        match ascii {
+            Some(c) => {
+                normalized.push(c);
+                i += 2;
+            }
+            // other double byte characters
+            None if matches!(a, 0x81..=0x9F | 0xE0..=0xFC) && b != 0 => {
+                normalized.extend_from_slice(&[a, b]);
+                i += 2;
+            }
+            None => {
+                normalized.push(a);
+                i += 1;
+            }
+        }
+    }
+    normalized
+}
+
+fn name_matches(slp_name: &[u8], name: &[u8; 31]) -> bool {
+    let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    normalize_slp_name(slp_name) == name[..len]
 }
 
 pub type ReplayFlags = u64;
 pub mod replay_flags {
     use super::ReplayFlags;
     pub const SWAP_SHEIK_ZELDA: ReplayFlags = 1 << 0;
+    /// If the export frame is during a KO, export from the respawn instead of just before the KO.
+    pub const SKIP_TO_RESPAWN: ReplayFlags = 1 << 1;
 }
 
+/// Slippi frame number of the first frame in an slp. 'GO' disappears on frame 0.
+pub const FIRST_SLIPPI_FRAME: i32 = -123;
+
+/// Default starting time on the game timer, in seconds.
+/// slp_parser does not give us the timer setting, so exports by timer assume the usual 8 minutes.
+pub const DEFAULT_TIMER_SECONDS: u32 = 8 * 60;
+
+/// A frame in a game, in any of the ways players refer to one.
+///
+/// All are resolved to an index into the slp frames:
+/// - `Index(i)` is `i`.
+/// - `SlippiFrame(f)` is `f + 123`. This is what Slippi tools show, and what `RecordingState::start_frame` stores.
+/// - `Timer` is the frame the in-game timer first shows `seconds` remaining, i.e. `mm:ss.00`.
+///   The timer starts counting down on Slippi frame 0, so this is `(timer_start - seconds) * 60 + 123`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FrameAddress {
+    Index(usize),
+    SlippiFrame(i32),
+    Timer { seconds: u32, timer_start: u32 },
+}
+
+impl FrameAddress {
+    /// Parses "mm:ss" as a timer, "frame:N" as a Slippi frame and a plain number as an index.
+    /// Returns None if the address is malformed.
+    pub fn parse(s: &str, timer_start: u32) -> Option<FrameAddress> {
+        let s = s.trim();
+        if let Some(frame) = s.strip_prefix("frame:") {
+            return frame.trim().parse::<i32>().ok().map(FrameAddress::SlippiFrame);
+        }
+
+        match s.split_once(':') {
+            Some((m, sec)) => {
+                let m = m.parse::<u32>().ok()?;
+                let sec = sec.parse::<u32>().ok()?;
+                if sec >= 60 { return None; }
+                Some(FrameAddress::Timer { seconds: m * 60 + sec, timer_start })
+            }
+            None => s.parse::<usize>().ok().map(FrameAddress::Index),
+        }
+    }
+
+    /// Parses "mm:ss" as a number of seconds.
+    pub fn parse_timer_start(s: &str) -> Option<u32> {
+        match FrameAddress::parse(s, 0)? {
+            FrameAddress::Timer { seconds, .. } => Some(seconds),
+            _ => None,
+        }
+    }
+
+    /// Resolves to an index into the slp frames.
+    /// Returns `RecordingOutOfBounds` if the frame is before the start or after the end of the game.
+    pub fn resolve(self, game: &slp_parser::Game) -> Result<usize, ReplayCreationError> {
+        let game_len = game.frames.iter().flatten().map(|f| f.len()).max().unwrap_or(0);
+        self.resolve_in(game_len)
+    }
+
+    fn resolve_in(self, game_len: usize) -> Result<usize, ReplayCreationError> {
+        let idx = match self {
+            FrameAddress::Index(i) => i as i64,
+            FrameAddress::SlippiFrame(f) => f as i64 - FIRST_SLIPPI_FRAME as i64,
+            FrameAddress::Timer { seconds, timer_start } => {
+                (timer_start as i64 - seconds as i64) * 60 - FIRST_SLIPPI_FRAME as i64
+            }
+        };
+
+        if idx < 0 || idx as usize >= game_len { return Err(ReplayCreationError::RecordingOutOfBounds); }
+        Ok(idx as usize)
+    }
+}
+
+/// Formats the address so that `FrameAddress::parse` reads it back.
+impl std::fmt::Display for FrameAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            FrameAddress::Index(i) => write!(f, "{}", i),
+            FrameAddress::SlippiFrame(frame) => write!(f, "frame:{}", frame),
+            FrameAddress::Timer { seconds, .. } => write!(f, "{}:{:02}", seconds / 60, seconds % 60),
+        }
+    }
+}
+
+
+/// Returns the (low port, high port, whether the human is the low port) to export.
+/// With explicit ports, the human is exported as the low port.
+fn export_ports(game: &slp_parser::Game, human: HumanPort) -> Result<(usize, usize, bool), ReplayCreationError> {
+    match human {
+        HumanPort::Ports { human, cpu } => {
+            let in_game = |p: usize| p < 4 && game.frames[p].is_some();
+            if human == cpu || !in_game(human) || !in_game(cpu) {
+                return Err(ReplayCreationError::PlayerNotFound);
+            }
+            Ok((human, cpu, true))
+        }
+        HumanPort::ConnectCode(_) | HumanPort::Name(_) => {
+            let (low, high) = match game.info.low_high_ports() {
+                Some(p) => p,
+                None => return Err(ReplayCreationError::NotTwoPlayerGame),
+            };
+            let matches = |p: usize| match human {
+                HumanPort::ConnectCode(code) => game.info.connect_codes[p] == code,
+                HumanPort::Name(name) => name_matches(&game.info.names[p], &name),
+                _ => unreachable!(),
+            };
+            match (matches(low), matches(high)) {
+                (true, _) => Ok((low, high, true)),
+                (false, true) => Ok((low, high, false)),
+                (false, false) => Err(ReplayCreationError::PlayerNotFound),
+            }
+        }
+        HumanPort::HumanLowPort | HumanPort::HumanHighPort => match game.info.low_high_ports() {
+            Some((low, high)) => Ok((low, high, human == HumanPort::HumanLowPort)),
+            None => Err(ReplayCreationError::NotTwoPlayerGame),
+        },
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFighter {
+    Human,
+    HumanFollower,
+    Cpu,
+    CpuFollower,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportRange {
+    /// Indices into the slp frames.
+    pub frames: std::ops::Range<usize>,
+    /// None if a recording can start on any frame in the range.
+    /// Otherwise the first fighter that prevents it, and why.
+    pub unexportable: Option<(ExportFighter, Unexportable)>,
+}
+
+/// Splits the game into ranges of frames a recording can or can't start on,
+/// with the same players as `construct_tm_replay_from_slp` would export.
+///
+/// Starting on an unexportable frame isn't an error - `construct_tm_replay_from_slp`
+/// moves the start back to the last exportable frame, or after the entry animation.
+/// Special states are the exception, which fail with `SpecialActionState`.
+pub fn exportable_ranges(game: &slp_parser::Game, human: HumanPort) -> Result<Vec<ExportRange>, ReplayCreationError> {
+    let (low_port, high_port, human_is_low) = export_ports(game, human)?;
+    let (human_port, cpu_port) = if human_is_low { (low_port, high_port) } else { (high_port, low_port) };
+
+    let mut fighters = vec![
+        (ExportFighter::Human, &game.frames[human_port].as_ref().unwrap()[..]),
+        (ExportFighter::Cpu, &game.frames[cpu_port].as_ref().unwrap()[..]),
+    ];
+    if let Some(f) = game.follower_frames[human_port].as_ref() { fighters.push((ExportFighter::HumanFollower, &f[..])); }
+    if let Some(f) = game.follower_frames[cpu_port].as_ref() { fighters.push((ExportFighter::CpuFollower, &f[..])); }
+
+    let len = fighters.iter().map(|(_, f)| f.len()).min().unwrap_or(0);
+    Ok(merge_export_ranges((0..len).map(|i| {
+        fighters.iter().find_map(|(fighter, frames)| fighter_unexportable(&frames[i]).map(|reason| (*fighter, reason)))
+    })))
+}
+
+/// Merges runs of frames with the same reason into ranges.
+fn merge_export_ranges(
+    unexportable: impl Iterator<Item = Option<(ExportFighter, Unexportable)>>,
+) -> Vec<ExportRange> {
+    let mut ranges: Vec<ExportRange> = Vec::new();
+    for (i, unexportable) in unexportable.enumerate() {
+        match ranges.last_mut() {
+            Some(range) if range.unexportable == unexportable => range.frames.end = i + 1,
+            _ => ranges.push(ExportRange { frames: i..i+1, unexportable }),
+        }
+    }
+    ranges
+}
+
+/// A change `construct_tm_replay_from_slp` made to the requested frames.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Adjustment {
+    /// The start was moved forward past a KO, from `replay_flags::SKIP_TO_RESPAWN`.
+    SkippedToRespawn { frames: usize },
+    /// The start was moved back, as the fighter could not be exported on the requested frame
+    /// or on the frames searched back through. One for each fighter that held up the search.
+    /// `frame` is the latest frame the fighter could not be exported on, an index into the slp frames.
+    MovedBack { frames: usize, fighter: ExportFighter, reason: Unexportable, frame: usize },
+    /// The start was moved forward past the entry animation.
+    SkippedEntry { frames: usize },
+    /// The recording was cut short at the end of the game.
+    TruncatedToGameEnd { frames: usize },
+}
+
+/// Part of the exported state that is estimated rather than read from the slp.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Approximation {
+    /// The grab breakout timer is estimated from the percent and ignores mashing.
+    GrabTimer(ExportFighter),
+    /// Knockback magnitude is estimated from the launch velocity,
+    /// and the tumble and wiggle out flags are not restored.
+    Knockback(ExportFighter),
+    /// The follower's inputs don't match the leader's, so the follower AI is not restored exactly.
+    FollowerDesynced(ExportFighter),
+    /// The leader's inputs queued up for the follower are not restored,
+    /// so the follower idles for a few frames at the start of playback.
+    FollowerBuffer(ExportFighter),
+    /// Special action state variables are rebuilt from the action state history.
+    SpecialState(ExportFighter),
+    /// A special action state whose variables haven't been checked. Only the recorded state var is written.
+    UncheckedSpecialState(ExportFighter),
+    /// A stored charge, needles, oil or a copied ability is dropped, as where it is stored is unknown.
+    Resources(ExportFighter),
+    /// Whole body intangibility from the current action, such as a roll or tech, is not restored.
+    Intangibility(ExportFighter),
+    /// An airdodge or aerial special was used this airtime, and may be usable again after the export.
+    AirtimeSpecials(ExportFighter),
+    /// The walljump count since last touching the ground is counted from the action state history.
+    Walljumps(ExportFighter),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportReport {
+    /// Indices into the slp frames.
+    pub requested_frame: usize,
+    pub frame: usize,
+    pub requested_duration: usize,
+    pub duration: usize,
+    /// In the order they were made.
+    pub adjustments: Vec<Adjustment>,
+    pub approximations: Vec<Approximation>,
+}
 
 /// Construct TM replay from slp file.
 ///
-/// Returns GCI file bytes.
+/// Returns GCI file bytes, and a report of where the recording actually starts and what was approximated.
+///
+/// `frame` is an index into the slp frames. See `FrameAddress` for Slippi frame numbers and timer timestamps.
 ///
 /// # Unimplemented
 /// - items
 /// - animation blending
+/// - RNG seed. This can't be done: the TM savestate only holds the fighters (see SAVESTATE_SIZE),
+///   so the RNG comes from the match TM is running in.
+///   Random actions (Game & Watch's hammer, Peach's turnips, Luigi's misfire) will not match the slp.
 ///
 /// # Errors
+/// - If the game is not a 1v1 and ports are not given explicitly
+/// - If an explicitly given port, connect code or name is not in the game
+/// - If frame is past the end of the game
 /// - If duration is greater than 3600 frames
 /// - If name is longer than 31 bytes
 /// - If name is not ASCII
-/// - If either character is in a special action state (will be supported in the future)
+/// - If either character is in a special action state that can't be reconstructed,
+///   such as Kirby's swallow or controlling PK Thunder.
 /// - If Zelda is on cpu. This is due to a bug in Unclepunch.
 pub fn construct_tm_replay_from_slp(
     game: &slp_parser::Game, 
@@ -1686,20 +2347,20 @@ pub fn construct_tm_replay_from_slp(
     duration: usize,
     name: &str,
     flags: ReplayFlags,
-) -> Result<Vec<u8>, ReplayCreationError> {
+) -> Result<(Vec<u8>, ExportReport), ReplayCreationError> {
     let major = game.info.version_major;
     let minor = game.info.version_minor;
     if major < MIN_VERSION_MAJOR || (major == MIN_VERSION_MAJOR && minor < MIN_VERSION_MINOR) {
         return Err(ReplayCreationError::OutdatedReplay);
     }
     
+    let requested_frame = frame;
+    let requested_duration = duration;
     let mut frame = frame;
     let mut duration = duration;
+    let mut adjustments = Vec::new();
 
-    let (low_port, high_port) = match game.info.low_high_ports() {
-        Some(p) => p,
-        None => return Err(ReplayCreationError::NotTwoPlayerGame),
-    };
+    let (low_port, high_port, human_is_low) = export_ports(game, human)?;
     let low_port_frames = game.frames[low_port].as_ref().unwrap();
     let high_port_frames = game.frames[high_port].as_ref().unwrap();
     let low_follower_frames = game.follower_frames[low_port].as_ref();
@@ -1717,52 +2378,54 @@ pub fn construct_tm_replay_from_slp(
         frames.push(high_follower_frames);
     }
 
-    // search backwards for a good frame to export -------------------------
-
-    fn good_frame(f: &slp_parser::Frame) -> bool {
-        use slp_parser::{ActionState, StandardActionState::*};
-
-        if f.hitlag_frames != 0.0 { return false }
-
-        if matches!(f.state, ActionState::Standard(
-            CatchPull | CatchDashPull | CatchWait | CatchAttack | CatchCut
-                | ThrowF | ThrowB | ThrowHi | ThrowLw 
-                | CapturePulledHi | CaptureWaitHi | CaptureDamageHi | CapturePulledLw | CaptureWaitLw
-                | CaptureDamageLw | CaptureCut | CaptureJump | CaptureNeck | CaptureFoot
-                | ThrownF | ThrownB | ThrownHi | ThrownLw | ThrownLwWomen
-                | ShoulderedWait | ShoulderedWalkSlow | ShoulderedWalkMiddle | ShoulderedWalkFast | ShoulderedTurn
-                | ThrownFF | ThrownFB | ThrownFHi | ThrownFLw
-                | CaptureCaptain | CaptureYoshi | YoshiEgg | CaptureKoopa
-                | CaptureDamageKoopa | CaptureWaitKoopa | ThrownKoopaF | ThrownKoopaB
-                | CaptureKoopaAir | CaptureDamageKoopaAir | CaptureWaitKoopaAir | ThrownKoopaAirF | ThrownKoopaAirB
-                | CaptureKirby | CaptureWaitKirby | ThrownKirbyStar | ThrownCopyStar | ThrownKirby
-                | BarrelWait | Bury | BuryWait | BuryJump
-                | DamageSong | DamageSongWait | DamageSongRv | DamageBind
-                | DeadDown | DeadLeft | DeadRight | DeadUp | DeadUpStar | DeadUpStarIce | DeadUpFall
-                | DeadUpFallHitCamera | DeadUpFallHitCameraFlat | DeadUpFallIce | DeadUpFallHitCameraIce
-                | Rebirth | RebirthWait
-        )) {
-            return false;
+    // same order as frames
+    let side = |low: bool, follower: bool| match (low == human_is_low, follower) {
+        (true, false) => ExportFighter::Human,
+        (true, true) => ExportFighter::HumanFollower,
+        (false, false) => ExportFighter::Cpu,
+        (false, true) => ExportFighter::CpuFollower,
+    };
+    let mut fighters = vec![side(true, false), side(false, false)];
+    if low_follower_frames.is_some() { fighters.push(side(true, true)); }
+    if high_follower_frames.is_some() { fighters.push(side(false, true)); }
+
+    // Followers can have fewer frames than their leaders.
+    let frames_len = frames.iter().map(|f| f.len()).min().unwrap();
+    if frame >= frames_len { return Err(ReplayCreationError::RecordingOutOfBounds); }
+
+    if flags & replay_flags::SKIP_TO_RESPAWN != 0 {
+        let start = frame;
+        while frames.iter().any(|f| is_dead(f[frame].state)) {
+            if frame + 1 >= frames_len { return Err(ReplayCreationError::NoGoodExportFrame); }
+            frame += 1;
+            duration = duration.saturating_sub(1);
         }
-        
-        let state_num = f.state.as_u16() as usize;
-        if hitboxes::ATTACK_RANGE_START <= state_num && state_num < hitboxes::ATTACK_RANGE_END {
-            let hitbox_range = &hitboxes::ATTACK_HITBOXES[f.character as usize][state_num - hitboxes::ATTACK_RANGE_START];
-            if hitbox_range.contains(&(f.anim_frame as u32)) {
-                return false;
-            }
-        } 
-        
-        true
+        if frame != start { adjustments.push(Adjustment::SkippedToRespawn { frames: frame - start }); }
     }
 
+    // search backwards for a good frame to export -------------------------
+
+    let start = frame;
+    // Each fighter that held up the search, with the latest frame it couldn't be exported on.
+    let mut bad_fighters: Vec<(ExportFighter, Unexportable, usize)> = Vec::new();
+
     while frames.iter().any(|f| !good_frame(&f[frame])) {
+        for (f, fighter) in frames.iter().zip(fighters.iter()) {
+            if bad_fighters.iter().any(|(bad, _, _)| bad == fighter) { continue }
+            if let Some(reason) = frame_unexportable(&f[frame]) { bad_fighters.push((*fighter, reason, frame)); }
+        }
+
         if frame == 0 { return Err(ReplayCreationError::NoGoodExportFrame); }
         frame -= 1;
         duration += 1;
     }
 
+    for (fighter, reason, bad_frame) in bad_fighters {
+        adjustments.push(Adjustment::MovedBack { frames: start - frame, fighter, reason, frame: bad_frame });
+    }
+
     // We need to search forwards for entry
+    let before_entry = frame;
     while matches!(
         low_port_frames[frame].state, 
         slp_parser::ActionState::Standard(slp_parser::StandardActionState::Entry
@@ -1774,13 +2437,17 @@ pub fn construct_tm_replay_from_slp(
             | slp_parser::StandardActionState::EntryStart
             | slp_parser::StandardActionState::EntryEnd)
     ) {
+        if frame + 1 >= frames_len { return Err(ReplayCreationError::NoGoodExportFrame); }
         frame += 1;
     }
+    if frame != before_entry { adjustments.push(Adjustment::SkippedEntry { frames: frame - before_entry }); }
 
     // export ---------------------------------------------------------------
 
-    if frame + duration >= low_port_frames.len() {
-        duration = low_port_frames.len() - frame;
+    if frame + duration >= frames_len {
+        let truncated = frames_len - frame;
+        if truncated < duration { adjustments.push(Adjustment::TruncatedToGameEnd { frames: duration - truncated }); }
+        duration = truncated;
     }
 
     if name.len() >= 32 { return Err(ReplayCreationError::FilenameTooLong) }
@@ -1788,24 +2455,12 @@ pub fn construct_tm_replay_from_slp(
     if duration > 3600 { return Err(ReplayCreationError::DurationTooLong) }
 
     let info = &game.info;
-    let time = info.start_time.fields();
 
     let mut filename = [0u8; 31];
     filename[..name.len()].copy_from_slice(name.as_bytes());
 
     fn inputs_over_frames(frames: &[slp_parser::Frame], frame_i: usize) -> Vec<Input> {
-        let mut inputs: Vec<Input> = frames
-            .iter()
-            .map(|f| {
-                Input {
-                    button_flags: translate_buttons_from_slp(f.buttons_mask),
-                    stick_x: (f.left_stick_coords.x * 80.0) as i8,
-                    stick_y: (f.left_stick_coords.y * 80.0) as i8,
-                    cstick_x: (f.right_stick_coords.x * 80.0) as i8,
-                    cstick_y: (f.right_stick_coords.y * 80.0) as i8,
-                    trigger: (f.analog_trigger_value * 140.0) as u8,
-                }
-            }).collect();
+        let mut inputs: Vec<Input> = frames.iter().map(input_from_frame).collect();
         
         // characters will start moving on frame 84
         if frame_i < 83 {
@@ -1818,10 +2473,73 @@ pub fn construct_tm_replay_from_slp(
         inputs
     }
 
+    // Nana copies Popo's inputs a few frames late while synced.
+    // We look for the delay that explains Nana's recent inputs. If none does, she has desynced.
+    fn follower_state(
+        leader_frames: &[slp_parser::Frame],
+        frames: &[slp_parser::Frame],
+        frame_idx: usize,
+    ) -> FollowerState {
+        const SYNC_WINDOW: usize = 10;
+        const MAX_DELAY: usize = 30;
+
+        fn same_input(a: &slp_parser::Frame, b: &slp_parser::Frame) -> bool {
+            a.buttons_mask == b.buttons_mask
+                && a.left_stick_coords.x == b.left_stick_coords.x
+                && a.left_stick_coords.y == b.left_stick_coords.y
+                && a.right_stick_coords.x == b.right_stick_coords.x
+                && a.right_stick_coords.y == b.right_stick_coords.y
+                && a.analog_trigger_value == b.analog_trigger_value
+        }
+
+        // Early in the game there is less history, so we check what there is.
+        let delay = (0..MAX_DELAY).find(|&delay| {
+            let window = SYNC_WINDOW.min((frame_idx + 1).saturating_sub(delay));
+            window > 0 && (0..window).all(|i| same_input(&frames[frame_idx - i], &leader_frames[frame_idx - i - delay]))
+        });
+
+        let mut follower = FollowerState::SYNCED;
+        match delay {
+            Some(delay) => follower.delay = delay as u8,
+            None => follower.desynced = true,
+        }
+
+        follower
+    }
+
+    fn grab_state(
+        frames: &[slp_parser::Frame],
+        opponent_frames: &[slp_parser::Frame],
+        frame_idx: usize,
+    ) -> Option<GrabState> {
+        let frame = &frames[frame_idx];
+
+        if is_grabbing(frame.state) && is_grabbed(opponent_frames[frame_idx].state) {
+            return Some(GrabState { grabbed: false, timer: 0.0 });
+        }
+
+        if !is_grabbed(frame.state) || !is_grabbing(opponent_frames[frame_idx].state) {
+            return None;
+        }
+
+        let held_frames = frames[..frame_idx].iter().rev()
+            .take_while(|f| is_grabbed(f.state))
+            .count();
+        let grab_start = &frames[frame_idx - held_frames];
+
+        // This is an estimate, not the game's formula: the timer grows with the percent at the time of the grab.
+        // Mashing before the export frame is not accounted for either.
+        // Reported as Approximation::GrabTimer.
+        let timer = 90.0 + 1.7 * grab_start.percent - held_frames as f32;
+        Some(GrabState { grabbed: true, timer: timer.max(0.0) })
+    }
+
     fn state(
         starting_char: slp_parser::CharacterColour, 
         frames: &[slp_parser::Frame],
         opponent_frames: &[slp_parser::Frame],
+        opponent_follower_frames: Option<&[slp_parser::Frame]>,
+        leader_frames: Option<&[slp_parser::Frame]>,
         frame_idx: usize,
     ) -> CharacterState {
         let frame = &frames[frame_idx];
@@ -1913,63 +2631,17 @@ pub fn construct_tm_replay_from_slp(
                 char_state_var[12..16].copy_from_slice(&40.0f32.to_be_bytes());
             }
 
-            slp_parser::ActionState::Special(slp_parser::SpecialActionState::Marth(
-                slp_parser::SpecialActionStateMarth::DolphinSlashGround
-                | slp_parser::SpecialActionStateMarth::DolphinSlashAir
-            )) => {
-                if frame.anim_frame >= 6.0 { subaction_flags[3] = 1; }
-            }
-
-            slp_parser::ActionState::Special(slp_parser::SpecialActionState::Roy(
-                slp_parser::SpecialActionStateRoy::BlazerGround
-                | slp_parser::SpecialActionStateRoy::BlazerAir
-            )) => {
-                if frame.anim_frame >= 10.0 { subaction_flags[3] = 1; }
-            }
-            
-            slp_parser::ActionState::Special(
-                slp_parser::SpecialActionState::Jigglypuff(
-                    slp_parser::SpecialActionStateJigglypuff::Jump2
-                    | slp_parser::SpecialActionStateJigglypuff::Jump3
-                    | slp_parser::SpecialActionStateJigglypuff::Jump4
-                    | slp_parser::SpecialActionStateJigglypuff::Jump5
-                ) | slp_parser::SpecialActionState::Kirby(
-                    slp_parser::SpecialActionStateKirby::Jump2
-                    | slp_parser::SpecialActionStateKirby::Jump3
-                    | slp_parser::SpecialActionStateKirby::Jump4
-                    | slp_parser::SpecialActionStateKirby::Jump5
-                )
-            ) => subaction_flags[3] = if frame.anim_frame >= 28.0 { 1 } else { 0 },
-
-            slp_parser::ActionState::Special(slp_parser::SpecialActionState::Peach(
-                slp_parser::SpecialActionStatePeach::Float
-                | slp_parser::SpecialActionStatePeach::FloatNair
-                | slp_parser::SpecialActionStatePeach::FloatFair
-                | slp_parser::SpecialActionStatePeach::FloatBair
-                | slp_parser::SpecialActionStatePeach::FloatUair
-                | slp_parser::SpecialActionStatePeach::FloatDair
-            )) => {
-                let mut first_float_frame = frame_idx - 1;
-                while first_float_frame > 0 {
-                    if matches!(frames[first_float_frame].state,
-                        slp_parser::ActionState::Special(slp_parser::SpecialActionState::Peach(
-                            slp_parser::SpecialActionStatePeach::Float
-                            | slp_parser::SpecialActionStatePeach::FloatNair
-                            | slp_parser::SpecialActionStatePeach::FloatFair
-                            | slp_parser::SpecialActionStatePeach::FloatBair
-                            | slp_parser::SpecialActionStatePeach::FloatUair
-                            | slp_parser::SpecialActionStatePeach::FloatDair))
-                    ) {
-                        first_float_frame -= 1;
-                    } else {
-                        first_float_frame += 1;
-                        break;
-                    }
+            slp_parser::ActionState::Special(_) => {
+                let support = special_states::support(frame.character, frame.state_num);
+                if let special_states::Support::Reconstruct(reconstruct) = support {
+                    let mut vars = special_states::SpecialVars { char_fighter_var, char_state_var, subaction_flags };
+                    reconstruct(frames, frame_idx, &mut vars);
+                    char_fighter_var = vars.char_fighter_var;
+                    char_state_var = vars.char_state_var;
+                    subaction_flags = vars.subaction_flags;
                 }
-
-                let float_frames_left = 150 - (frame_idx - first_float_frame);
-                char_fighter_var[4..8].copy_from_slice(&(float_frames_left as f32).to_be_bytes());
             }
+
             _ => (),
         }
         
@@ -1995,33 +2667,51 @@ pub fn construct_tm_replay_from_slp(
             char_fighter_var[0..4].copy_from_slice(&has_float.to_be_bytes());
         }
 
-        let frames_since_hit = match frame.state {
-            slp_parser::ActionState::Standard(slp_parser::StandardActionState::DamageHi1        
-                | slp_parser::StandardActionState::DamageHi2    
-                | slp_parser::StandardActionState::DamageHi3    
-                | slp_parser::StandardActionState::DamageN1     
-                | slp_parser::StandardActionState::DamageN2     
-                | slp_parser::StandardActionState::DamageN3     
-                | slp_parser::StandardActionState::DamageLw1    
-                | slp_parser::StandardActionState::DamageLw2    
-                | slp_parser::StandardActionState::DamageLw3    
-                | slp_parser::StandardActionState::DamageAir1   
-                | slp_parser::StandardActionState::DamageAir2   
-                | slp_parser::StandardActionState::DamageAir3   
-                | slp_parser::StandardActionState::DamageFlyHi  
-                | slp_parser::StandardActionState::DamageFlyN   
-                | slp_parser::StandardActionState::DamageFlyLw  
-                | slp_parser::StandardActionState::DamageFlyTop 
-                | slp_parser::StandardActionState::DamageFlyRoll)
-            => frames[..frame_idx].iter().rev().position(|f| f.hitlag_frames != 0.0).unwrap() as i32,
-            _ => -1 
+        // Per-airtime flags. Fastfall is recorded in the state flags.
+        // Airdodges and aerial specials are only tracked for the report,
+        // as where their per-airtime flags are stored is unknown.
+        let airtime = frames[..=frame_idx].iter().rev().take_while(|f| f.is_airborne).count();
+        let airtime_frames = &frames[frame_idx + 1 - airtime..=frame_idx];
+        let walljumps_used = airtime_frames.windows(2)
+            .filter(|fs| fs[0].state != fs[1].state)
+            .filter(|fs| fs[1].state == slp_parser::ActionState::Standard(slp_parser::StandardActionState::PassiveWallJump))
+            .count() as u8;
+        let air_specials = char_data::AIR_SPECIALS[frame.character as usize];
+        let airtime_specials_used = airtime_frames.iter().any(|f| {
+            f.state == slp_parser::ActionState::Standard(slp_parser::StandardActionState::EscapeAir)
+                || air_specials.iter().any(|(first, last)| *first <= f.state_num && f.state_num <= *last)
+        });
+
+        let in_knockback = in_knockback(frame.state);
+
+        let in_hitlag = frame.hitlag_frames != 0.0;
+        let frames_since_hit = match in_knockback {
+            true if in_hitlag => 0,
+            true => frames[..frame_idx].iter().rev().position(|f| f.hitlag_frames != 0.0).unwrap_or(0) as i32,
+            false => -1,
+        };
+
+        let hit_velocity = frame.hit_velocity;
+
+        // Estimated as 0.03 launch speed per unit of knockback. Reported as Approximation::Knockback.
+        // Hitstun remaining is the first char state var, which the slp records (hitstun_misc).
+        // The tumble and wiggle out flags are not reconstructed.
+        let launch_velocity = match in_knockback {
+            true if in_hitlag => hit_velocity,
+            true => frames[frame_idx - frames_since_hit as usize].hit_velocity,
+            false => frame.hit_velocity,
+        };
+        let knockback = match in_knockback {
+            true => (launch_velocity.x*launch_velocity.x + launch_velocity.y*launch_velocity.y).sqrt() / 0.03,
+            false => 0.0,
         };
-        
-        // TODO pass in followers
-        let stale_moves = slp_parser::compute_staled_moves(
-            &frames[..=frame_idx],
-            &[&opponent_frames[..=frame_idx]],
-        );
+
+        // For Ice Climbers, this is replaced with both climbers' hits after the states are built.
+        let mut opponents = vec![&opponent_frames[..=frame_idx]];
+        if let Some(opponent_follower_frames) = opponent_follower_frames {
+            opponents.push(&opponent_follower_frames[..=frame_idx]);
+        }
+        let stale_moves = slp_parser::compute_staled_moves(&frames[..=frame_idx], &opponents);
 
         let mut offscreen_damage_timer = 0;
         let mut i = frame_idx;
@@ -2132,6 +2822,12 @@ pub fn construct_tm_replay_from_slp(
             }
         }
         
+        let shield_light = if frame.buttons_mask & (slp_parser::buttons_mask::L_DIGITAL | slp_parser::buttons_mask::R_DIGITAL) != 0 {
+            1.0
+        } else {
+            frame.analog_trigger_value
+        };
+
         CharacterState {
             // respect zelda/sheik transformation
             character: slp_parser::CharacterColour::from_character_and_colour(
@@ -2146,14 +2842,19 @@ pub fn construct_tm_replay_from_slp(
             state_speed,
             direction: frame.direction,
             percent: frame.percent,
+            stocks: frame.stock_count,
             self_velocity: [frame.velocity.x, frame.velocity.y, 0.0],
-            hit_velocity: [frame.hit_velocity.x, frame.hit_velocity.y, 0.0],
+            hit_velocity: [hit_velocity.x, hit_velocity.y, 0.0],
             ground_velocity: [frame.ground_x_velocity, 0.0, 0.0],
             frames_since_hit,
             char_fighter_var,
             char_state_var,
             jumps_remaining: frame.jumps_remaining,
+            walljumps_used,
+            airtime_specials_used,
             hitlag_frames_left: frame.hitlag_frames,
+            knockback,
+            hit_by_opponent: in_knockback && frame.last_hit_by == opponent_frames[frame_idx].port_idx,
             subaction_flags,
             state_flags: frame.state_flags,
             stale_moves,
@@ -2171,18 +2872,78 @@ pub fn construct_tm_replay_from_slp(
             smash_attack,
             intang_ledge,
             intang_respawn,
-
-            // state_blend, x_rotn_rot, anim_velocity
+            intang_action: intangibility::is_intangible(frame.state_num, frame.anim_frame),
+            shield_health: frame.shield_size,
+            shield_light,
+            grab: grab_state(frames, opponent_frames, frame_idx),
+            follower: leader_frames.map(|leader| follower_state(leader, frames, frame_idx)),
+            stored_resources: resources::stored(frames, frame_idx),
+
+            // state_blend, anim_velocity
             ..Default::default()
         }
     }
 
-    let low_state = state(low_starting_character, low_port_frames, high_port_frames, frame);
-    let high_state = state(high_starting_character, high_port_frames, low_port_frames, frame);
-    let low_follower_state = low_follower_frames
-        .map(|f| state(low_starting_character, f, high_port_frames, frame));
-    let high_follower_state = high_follower_frames
-        .map(|f| state(high_starting_character, f, low_port_frames, frame));
+    // There is one stale move queue per player - the savestate has one per FtState, after both climbers -
+    // so Nana's hits go into the same queue as Popo's.
+    // Each fighter's queue is computed separately, then the hits are merged in the order they landed.
+    fn merged_stale_moves(
+        leader_frames: &[slp_parser::Frame],
+        follower_frames: &[slp_parser::Frame],
+        opponent_frames: &[slp_parser::Frame],
+        opponent_follower_frames: Option<&[slp_parser::Frame]>,
+        frame_idx: usize,
+    ) -> [slp_parser::StaleMove; 10] {
+        let queue = |frames: &[slp_parser::Frame], t: usize| {
+            let mut opponents = vec![&opponent_frames[..=t]];
+            if let Some(opponent_follower_frames) = opponent_follower_frames {
+                opponents.push(&opponent_follower_frames[..=t]);
+            }
+            slp_parser::compute_staled_moves(&frames[..=t], &opponents)
+        };
+        let contains = |queue: &[slp_parser::StaleMove; 10], m: &slp_parser::StaleMove| {
+            queue.iter().any(|q| q.attack == m.attack && q.instance_id == m.instance_id)
+        };
+
+        // (frame of the hit, hit)
+        let mut hits = Vec::with_capacity(20);
+        for frames in [leader_frames, follower_frames] {
+            let last = queue(frames, frame_idx);
+            for m in last.iter().filter(|m| m.attack != slp_parser::AttackKind::Null) {
+                // A hit stays in the queue from when it lands until it is pushed out,
+                // so the first frame it is in the queue can be found by bisection.
+                let (mut lo, mut hi) = (0, frame_idx);
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    if contains(&queue(frames, mid), m) { hi = mid } else { lo = mid + 1 }
+                }
+                hits.push((lo, *m));
+            }
+        }
+        hits.sort_by_key(|(hit_frame, _)| *hit_frame);
+
+        let mut stale_moves = [slp_parser::StaleMove::NULL; 10];
+        let newest = &hits[hits.len().saturating_sub(10)..];
+        for (i, (_, m)) in newest.iter().enumerate() {
+            stale_moves[i] = *m;
+        }
+        stale_moves
+    }
+
+    let low_followers = low_follower_frames.map(|f| &f[..]);
+    let high_followers = high_follower_frames.map(|f| &f[..]);
+    let mut low_state = state(low_starting_character, low_port_frames, high_port_frames, high_followers, None, frame);
+    let mut high_state = state(high_starting_character, high_port_frames, low_port_frames, low_followers, None, frame);
+    if let Some(f) = low_followers {
+        low_state.stale_moves = merged_stale_moves(low_port_frames, f, high_port_frames, high_followers, frame);
+    }
+    if let Some(f) = high_followers {
+        high_state.stale_moves = merged_stale_moves(high_port_frames, f, low_port_frames, low_followers, frame);
+    }
+    let low_follower_state = low_followers
+        .map(|f| state(low_starting_character, f, high_port_frames, high_followers, Some(&low_port_frames[..]), frame));
+    let high_follower_state = high_followers
+        .map(|f| state(high_starting_character, f, low_port_frames, low_followers, Some(&high_port_frames[..]), frame));
 
     let hmn_frames;
     let hmn_state;
@@ -2190,8 +2951,8 @@ pub fn construct_tm_replay_from_slp(
     let cpu_frames;
     let cpu_state;
     let cpu_follower_state;
-    match human {
-        HumanPort::HumanLowPort => {
+    match human_is_low {
+        true => {
             hmn_frames = low_port_frames;
             cpu_frames = high_port_frames;
             hmn_state = low_state;
@@ -2199,7 +2960,7 @@ pub fn construct_tm_replay_from_slp(
             hmn_follower_state = low_follower_state;
             cpu_follower_state = high_follower_state;
         },
-        HumanPort::HumanHighPort => {
+        false => {
             cpu_frames = low_port_frames;
             hmn_frames = high_port_frames;
             cpu_state = low_state;
@@ -2209,23 +2970,58 @@ pub fn construct_tm_replay_from_slp(
         },
     };
 
+    let mut approximations = Vec::new();
+    for (fighter, st) in [
+        (ExportFighter::Human, Some(&hmn_state)),
+        (ExportFighter::HumanFollower, hmn_follower_state.as_ref()),
+        (ExportFighter::Cpu, Some(&cpu_state)),
+        (ExportFighter::CpuFollower, cpu_follower_state.as_ref()),
+    ] {
+        let st = match st {
+            Some(st) => st,
+            None => continue,
+        };
+        let character = st.character.character();
+
+        if st.grab.is_some_and(|g| g.grabbed) { approximations.push(Approximation::GrabTimer(fighter)); }
+        if st.knockback != 0.0 { approximations.push(Approximation::Knockback(fighter)); }
+        match st.follower {
+            Some(f) if f.desynced => approximations.push(Approximation::FollowerDesynced(fighter)),
+            Some(f) if f.delay > 0 => approximations.push(Approximation::FollowerBuffer(fighter)),
+            _ => (),
+        }
+        match special_states::support(character, st.state.as_u16()) {
+            special_states::Support::Reconstruct(_) => approximations.push(Approximation::SpecialState(fighter)),
+            special_states::Support::Unchecked => approximations.push(Approximation::UncheckedSpecialState(fighter)),
+            _ => (),
+        }
+        if st.stored_resources {
+            approximations.push(Approximation::Resources(fighter));
+        }
+        if st.intang_action { approximations.push(Approximation::Intangibility(fighter)); }
+        if st.airtime_specials_used { approximations.push(Approximation::AirtimeSpecials(fighter)); }
+        if st.walljumps_used > 0 { approximations.push(Approximation::Walljumps(fighter)); }
+    }
+
+    let report = ExportReport {
+        requested_frame,
+        frame,
+        requested_duration,
+        duration,
+        adjustments,
+        approximations,
+    };
+
     let inputs_range = if duration == 0 {
         frame..frame
     } else {
         frame+1..frame+duration
     };
 
-    construct_tm_replay(
+    let gci = construct_tm_replay(
         &RecordingState {
             stage: info.stage,
-            time: RecordingTime {
-                year: time.year,
-                month: time.month,
-                day: time.day,
-                hour: time.hour,
-                minute: time.minute,
-                second: time.second,
-            },
+            time: RecordingTime::from_slp(game),
             filename,
             menu_settings: RecordingMenuSettings {
                 hmn_mode: HmnRecordingMode::Playback,
@@ -2251,8 +3047,10 @@ pub fn construct_tm_replay_from_slp(
                 None, None, None, None, None
             ],
         },
-        flags
-    )
+        flags,
+    )?;
+
+    Ok((gci, report))
 }
 
 pub struct ReadReplayData {
@@ -2305,6 +3103,15 @@ fn frame_from_ft_state(ft_state: &[u8], port_idx: u8) -> slp_parser::Frame {
     }
 }
 
+/// Returns None if the RecordingSave is corrupt or larger than the one we write.
+fn read_recording_save(recording_compressed_save: &[u8]) -> Option<Vec<u8>> {
+    let uncompressed_size = u32::from_be_bytes(recording_compressed_save.get(0..4)?.try_into().unwrap()) as usize;
+    if uncompressed_size > RECORDING_SIZE { return None; }
+    let mut recording_save = vec![0u8; uncompressed_size];
+    compress::lz77_decompress(recording_compressed_save, recording_save.as_mut_slice())?;
+    Some(recording_save)
+}
+
 /// Construct a pseudo game from a gci replay.
 pub fn read_tm_replay(gci_bytes: &mut [u8]) -> Option<ReadReplayData> {
     let replay_buffer = read_replay_buffer(gci_bytes)?;
@@ -2316,22 +3123,19 @@ pub fn read_tm_replay(gci_bytes: &mut [u8]) -> Option<ReadReplayData> {
     let char_hmn = slp_parser::CharacterColour::from_character_and_colour(char_hmn, replay_buffer[9])?;
     let char_cpu = slp_parser::CharacterColour::from_character_and_colour(char_cpu, replay_buffer[11])?;
 
-    let recording_compressed_save = &replay_buffer[recording_offset..menu_offset];
-    let uncompressed_size = u32::from_be_bytes(recording_compressed_save[0..4].try_into().unwrap()) as usize;
-    let mut recording_save = vec![0u8; uncompressed_size + 257]; // pad a bit for compression algo
-    compress::lz77_decompress(recording_compressed_save, recording_save.as_mut_slice());
+    let recording_save = read_recording_save(replay_buffer.get(recording_offset..menu_offset)?)?;
     
     let st_offset = 312; // savestate offset - skip MatchInit in RecordingSave
     let ft_state_offset = 8+EVENT_DATASIZE; // FtState array offset - fields in Savestate;
-    let ft_state_size = 9016;
+    let ft_state_size = FT_STATE_SIZE;
     
-    let ft_state_hmn = &recording_save[st_offset+ft_state_offset..][..ft_state_size]; 
-    let ft_state_cpu = &recording_save[st_offset+ft_state_offset+ft_state_size..][..ft_state_size];
+    let ft_state_hmn = recording_save.get(st_offset+ft_state_offset..)?.get(..ft_state_size)?; 
+    let ft_state_cpu = recording_save.get(st_offset+ft_state_offset+ft_state_size..)?.get(..ft_state_size)?;
     
     let hmn_frame = frame_from_ft_state(ft_state_hmn, 0);
     let cpu_frame = frame_from_ft_state(ft_state_cpu, 1);
     
-    let stage_external = u16::from_be_bytes(recording_save[0xE..][..2].try_into().unwrap());
+    let stage_external = u16::from_be_bytes(recording_save.get(0xE..0x10)?.try_into().unwrap());
     let stage = slp_parser::Stage::from_u16(stage_external)?;
     
     let pseudo_game = slp_parser::Game {
@@ -2371,3 +3175,291 @@ pub fn read_tm_replay(gci_bytes: &mut [u8]) -> Option<ReadReplayData> {
         name,
     })
 }
+
+/// Everything decoded from a gci replay. See `inspect_tm_replay`.
+#[derive(Clone, Debug)]
+pub struct ReplayInspection {
+    /// Name of the file on the memory card.
+    pub gci_name: String,
+    /// Name shown in TM.
+    pub name: String,
+    pub version: u16,
+    pub hmn_character: slp_parser::CharacterColour,
+    pub cpu_character: slp_parser::CharacterColour,
+    pub stage: slp_parser::Stage,
+    pub time: RecordingTime,
+    pub menu_settings: [u8; 6],
+    pub hmn_state: FtStateInspection,
+    pub cpu_state: FtStateInspection,
+    pub hmn_slots: [InputSlotInspection; REC_SLOTS],
+    pub cpu_slots: [InputSlotInspection; REC_SLOTS],
+}
+
+/// The main fighter of an FtState. Only the fields we write are decoded.
+#[derive(Clone, Debug)]
+pub struct FtStateInspection {
+    pub character: slp_parser::Character,
+    pub costume: u8,
+    pub stocks: u8,
+    pub state_num: u16,
+    pub state_frame: f32,
+    pub state_speed: f32,
+    pub direction: slp_parser::Direction,
+    pub position: [f32; 2],
+    pub self_velocity: [f32; 2],
+    pub hit_velocity: [f32; 2],
+    pub percent: f32,
+    pub hitlag_frames_left: f32,
+    pub jumps_used: u8,
+    pub shield_health: f32,
+    /// (attack kind, instance id)
+    pub stale_moves: [(u8, u16); 10],
+}
+
+/// Summary of a recording slot. Slots without inputs have a start frame of -1.
+#[derive(Copy, Clone, Debug)]
+pub struct InputSlotInspection {
+    pub start_frame: i32,
+    pub num_frames: u32,
+    /// Number of frames with any button pressed, stick or trigger held.
+    pub active_frames: u32,
+}
+
+fn ft_state_inspection(ft_state: &[u8]) -> Option<FtStateInspection> {
+    fn read_f32(ft_state: &[u8], offset: usize) -> f32 {
+        f32::from_be_bytes(ft_state[offset..][..4].try_into().unwrap())
+    }
+    
+    fn read_u32(ft_state: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(ft_state[offset..][..4].try_into().unwrap())
+    }
+
+    // same offsets as in construct_tm_replay
+    let ft_savestate_data_size = 4396;
+    let playerblock_offset = ft_savestate_data_size*2;
+    let stale_offset = 8972;
+    let state_offset = 4;
+    let phys_offset = 40;
+    let dmg_offset = 3680;
+    let jump_offset = 4048;
+    let shield_offset = 4108;
+
+    let character = slp_parser::Character::from_u8_external(read_u32(ft_state, playerblock_offset + 4) as u8)?;
+    let direction = if read_f32(ft_state, state_offset + 4) == -1.0 { 
+        slp_parser::Direction::Left 
+    } else { 
+        slp_parser::Direction::Right 
+    };
+
+    let stale_moves = std::array::from_fn(|i| {
+        let offset = stale_offset + 4 + 4*i;
+        (ft_state[offset+1], u16::from_be_bytes(ft_state[offset+2..][..2].try_into().unwrap()))
+    });
+
+    Some(FtStateInspection {
+        character,
+        costume: ft_state[playerblock_offset + 68],
+        stocks: ft_state[playerblock_offset + 0x8E],
+        state_num: read_u32(ft_state, state_offset) as u16,
+        state_frame: read_f32(ft_state, state_offset + 8),
+        state_speed: read_f32(ft_state, state_offset + 12),
+        direction,
+        position: [read_f32(ft_state, phys_offset + 60), read_f32(ft_state, phys_offset + 64)],
+        self_velocity: [read_f32(ft_state, phys_offset + 12), read_f32(ft_state, phys_offset + 16)],
+        hit_velocity: [read_f32(ft_state, phys_offset + 24), read_f32(ft_state, phys_offset + 28)],
+        percent: read_f32(ft_state, dmg_offset + 4) * 2.0, // percent is stored halved
+        hitlag_frames_left: read_f32(ft_state, dmg_offset + 304),
+        jumps_used: ft_state[jump_offset],
+        shield_health: read_f32(ft_state, shield_offset),
+        stale_moves,
+    })
+}
+
+fn input_slot_inspection(slot: &[u8]) -> InputSlotInspection {
+    let start_frame = i32::from_be_bytes(slot[0..4].try_into().unwrap());
+    let num_frames = u32::from_be_bytes(slot[4..8].try_into().unwrap());
+    let active_frames = (0..(num_frames as usize).min(REC_LENGTH))
+        .filter(|i| slot[8 + i*6..][..6].iter().any(|b| *b != 0))
+        .count() as u32;
+
+    InputSlotInspection { start_frame, num_frames, active_frames }
+}
+
+/// Decode the headers, menu settings, fighter states and input slots of a gci replay.
+pub fn inspect_tm_replay(gci_bytes: &mut [u8]) -> Option<ReplayInspection> {
+    fn c_str(bytes: &[u8]) -> String {
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..len]).into_owned()
+    }
+
+    if gci_bytes.len() < 0x1EB0 { return None; }
+    let gci_name = c_str(&gci_bytes[8..0x28]);
+    let name = c_str(&gci_bytes[0x60..][..0x20]);
+
+    let replay_buffer = read_replay_buffer(gci_bytes)?;
+    let recording_offset = u32::from_be_bytes(replay_buffer[60..64].try_into().unwrap()) as usize;
+    let menu_offset = u32::from_be_bytes(replay_buffer[64..68].try_into().unwrap()) as usize;
+    if menu_offset + 6 > replay_buffer.len() || recording_offset >= menu_offset { return None; }
+
+    let hmn_character = slp_parser::Character::from_u8_external(replay_buffer[8])?;
+    let cpu_character = slp_parser::Character::from_u8_external(replay_buffer[10])?;
+    let hmn_character = slp_parser::CharacterColour::from_character_and_colour(hmn_character, replay_buffer[9])?;
+    let cpu_character = slp_parser::CharacterColour::from_character_and_colour(cpu_character, replay_buffer[11])?;
+    let stage = slp_parser::Stage::from_u16(u16::from_be_bytes(replay_buffer[12..14].try_into().unwrap()))?;
+
+    let time = RecordingTime {
+        month: replay_buffer[16],
+        day: replay_buffer[17],
+        year: u16::from_be_bytes(replay_buffer[18..20].try_into().unwrap()),
+        hour: replay_buffer[20],
+        minute: replay_buffer[21],
+        second: replay_buffer[22],
+    };
+
+    let recording_save = read_recording_save(replay_buffer.get(recording_offset..menu_offset)?)?;
+
+    let st_offset = MATCHINIT_SIZE;
+    let ft_state_offset = 8+EVENT_DATASIZE;
+    let ft_state_size = FT_STATE_SIZE;
+    let hmn_state = ft_state_inspection(recording_save.get(st_offset+ft_state_offset..)?.get(..ft_state_size)?)?;
+    let cpu_state = ft_state_inspection(recording_save.get(st_offset+ft_state_offset+ft_state_size..)?.get(..ft_state_size)?)?;
+
+    let rec_start = SAVESTATE_SIZE+MATCHINIT_SIZE;
+    let slots = recording_save.get(rec_start..)?.get(..2*REC_SLOTS*REC_SLOT_SIZE)?;
+    let slot = |i: usize| input_slot_inspection(&slots[i*REC_SLOT_SIZE..][..REC_SLOT_SIZE]);
+
+    Some(ReplayInspection {
+        gci_name,
+        name,
+        version: u16::from_be_bytes(replay_buffer[0..2].try_into().unwrap()),
+        hmn_character,
+        cpu_character,
+        stage,
+        time,
+        menu_settings: replay_buffer[menu_offset..][..6].try_into().unwrap(),
+        hmn_state,
+        cpu_state,
+        hmn_slots: std::array::from_fn(|i| slot(i)),
+        cpu_slots: std::array::from_fn(|i| slot(i + REC_SLOTS)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slp_name(bytes: &[u8]) -> [u8; 31] {
+        let mut name = [0u8; 31];
+        name[..bytes.len()].copy_from_slice(bytes);
+        name
+    }
+
+    fn name_bytes(name: &str) -> [u8; 31] {
+        match HumanPort::name(name) {
+            Some(HumanPort::Name(bytes)) => bytes,
+            _ => panic!("'{}' is not a valid name", name),
+        }
+    }
+
+    #[test]
+    fn name_fullwidth_input() {
+        assert_eq!(HumanPort::name("ＡＢＣ１"), HumanPort::name("ABC1"));
+        assert_eq!(HumanPort::name("Ａ\u{3000}Ｂ"), HumanPort::name("A B"));
+        assert_eq!(HumanPort::name("ぷりん"), None);
+        assert_eq!(HumanPort::name(&"a".repeat(32)), None);
+    }
+
+    #[test]
+    fn name_matches_shift_jis() {
+        // "ＡＢＣ＃１" in Shift-JIS
+        let name = slp_name(&[0x82, 0x60, 0x82, 0x61, 0x82, 0x62, 0x81, 0x94, 0x82, 0x50]);
+        assert!(name_matches(&name, &name_bytes("ABC#1")));
+        assert!(name_matches(&name, &name_bytes("ＡＢＣ＃１")));
+        assert!(!name_matches(&name, &name_bytes("ABC")));
+
+        assert!(name_matches(&slp_name(b"fox"), &name_bytes("fox")));
+        assert!(!name_matches(&slp_name(b"fox"), &name_bytes("FOX")));
+    }
+
+    #[test]
+    fn name_matches_kana() {
+        // "モ`A" in Shift-JIS. The second byte of "モ" and the "`" must not be read as "Ａ".
+        let name = slp_name(&[0x83, 0x82, 0x60, 0x41]);
+        assert!(!name_matches(&name, &name_bytes("A")));
+        assert_eq!(normalize_slp_name(&name), vec![0x83, 0x82, 0x60, 0x41]);
+    }
+
+    #[test]
+    fn connect_code() {
+        let mut expected = [0u8; 10];
+        expected[..8].copy_from_slice(&[b'A', b'B', b'C', b'D', 0x81, 0x94, b'1', b'2']);
+        assert_eq!(HumanPort::connect_code("abcd#12"), Some(HumanPort::ConnectCode(expected)));
+        assert_eq!(HumanPort::connect_code("ABCD#12"), Some(HumanPort::ConnectCode(expected)));
+
+        // the '#' takes 2 of the 10 bytes
+        assert!(HumanPort::connect_code("ABCDEFG#1").is_some());
+        assert!(HumanPort::connect_code("ABCDEFG#12").is_none());
+        assert!(HumanPort::connect_code("ＡBC#1").is_none());
+    }
+
+    #[test]
+    fn frame_address_parse() {
+        assert_eq!(FrameAddress::parse("1234", 480), Some(FrameAddress::Index(1234)));
+        assert_eq!(FrameAddress::parse(" frame:-123 ", 480), Some(FrameAddress::SlippiFrame(-123)));
+        assert_eq!(FrameAddress::parse("7:42", 480), Some(FrameAddress::Timer { seconds: 462, timer_start: 480 }));
+        assert_eq!(FrameAddress::parse("7:60", 480), None);
+        assert_eq!(FrameAddress::parse("-5", 480), None);
+        assert_eq!(FrameAddress::parse("frame:", 480), None);
+        assert_eq!(FrameAddress::parse_timer_start("8:00"), Some(480));
+        assert_eq!(FrameAddress::parse_timer_start("100"), None);
+
+        for addr in [FrameAddress::Index(5), FrameAddress::SlippiFrame(-10), FrameAddress::Timer { seconds: 62, timer_start: 480 }] {
+            assert_eq!(FrameAddress::parse(&addr.to_string(), 480), Some(addr));
+        }
+    }
+
+    #[test]
+    fn frame_address_resolve() {
+        assert!(matches!(FrameAddress::Index(99).resolve_in(100), Ok(99)));
+        assert!(matches!(FrameAddress::Index(100).resolve_in(100), Err(ReplayCreationError::RecordingOutOfBounds)));
+        assert!(matches!(FrameAddress::SlippiFrame(-123).resolve_in(100), Ok(0)));
+        assert!(matches!(FrameAddress::SlippiFrame(-124).resolve_in(100), Err(ReplayCreationError::RecordingOutOfBounds)));
+
+        // 7:59 is one second after the timer starts at Slippi frame 0
+        let timer = FrameAddress::Timer { seconds: 479, timer_start: 480 };
+        assert!(matches!(timer.resolve_in(1000), Ok(183)));
+        let timer = FrameAddress::Timer { seconds: 0, timer_start: 480 };
+        assert!(matches!(timer.resolve_in(1000), Err(ReplayCreationError::RecordingOutOfBounds)));
+    }
+
+    #[test]
+    fn export_ranges_merge() {
+        let grab = Some((ExportFighter::Cpu, Unexportable::Grab));
+        let dead = Some((ExportFighter::Human, Unexportable::Dead));
+        let ranges = merge_export_ranges([grab, grab, None, None, None, dead, grab].into_iter());
+        assert_eq!(ranges, vec![
+            ExportRange { frames: 0..2, unexportable: grab },
+            ExportRange { frames: 2..5, unexportable: None },
+            ExportRange { frames: 5..6, unexportable: dead },
+            ExportRange { frames: 6..7, unexportable: grab },
+        ]);
+        assert!(merge_export_ranges(std::iter::empty()).is_empty());
+    }
+
+    #[test]
+    fn recording_save_corrupt() {
+        let mut save = vec![0u8; 64 + 257]; // pad a bit for compression algo
+        save[..64].iter_mut().enumerate().for_each(|(i, b)| *b = (i % 7) as u8);
+        let mut compressed = vec![0u8; 5 + 3*64];
+        let compressed_size = compress::lz77_compress(&save, 64, &mut compressed) as usize;
+        let compressed = &compressed[..compressed_size];
+        assert_eq!(read_recording_save(compressed).as_deref(), Some(&save[..64]));
+
+        assert!(read_recording_save(&compressed[..3]).is_none());
+        assert!(read_recording_save(&compressed[..compressed_size - 1]).is_none());
+
+        // pointer before the start of the save
+        assert!(read_recording_save(&[0, 0, 0, 1, 8, 0xFF, 0x00, 0]).is_none());
+        assert!(read_recording_save(&[0xFF, 0xFF, 0xFF, 0xFF, 8]).is_none());
+    }
+}
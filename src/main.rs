@@ -1,14 +1,93 @@
 use tm_replay::*;
 
+mod json;
+mod inspect;
+mod batch;
+mod search_cmd;
+mod markers_cmd;
+mod tail_cmd;
+mod punishes_cmd;
+mod exportable_cmd;
+mod report;
+
 const USAGE: &'static str = "Usage: tm_replay [OPTIONS] --slp-file <SLP_FILE> --start-frame <START_FRAME> --num-frames <NUM_FRAMES>
+       tm_replay inspect [--json] <GCI_FILE>
+       tm_replay batch [--output-dir <DIR>] [--json] <JOB_FILE>
+       tm_replay search [SEARCH_OPTIONS] <SLP_DIR>
+       tm_replay markers [MARKER_OPTIONS] <SLP_FILE>...
+       tm_replay tail [TAIL_OPTIONS] <SLP_FILE_OR_DIR>
+       tm_replay punishes [PUNISH_OPTIONS] <SLP_FILE>...
+       tm_replay exportable [--human <PLAYER>] [--json] <SLP_FILE>
 
 Options:
   -s, --slp-file <SLP_FILE>        Path to the input Slippi replay file
-  -f, --start-frame <START_FRAME>  Start frame for the recording
+  -f, --start-frame <START_FRAME>  Start frame for the recording. One of:
+                                     an index into the slp frames, e.g. 1234
+                                     a Slippi frame number, which starts at -123, e.g. frame:1111
+                                     a game timer timestamp, e.g. 7:42
+  --timer-start <MM:SS>            Starting time of the game timer, for timestamps [default: 8:00]
   -n, --num-frames <NUM_FRAMES>    Number of frames to record [default: 360]
   -o, --output-file <OUTPUT_FILE>  Output filepath for the savestate [default: new_recording.gci]
   -n, --name <NAME>                Name to give to the recording (max 31 ASCII characters) [default: new_recording]
+  --human-port <PORT>              Port (1-4) to export as the human player. Requires --cpu-port
+  --cpu-port <PORT>                Port (1-4) to export as the cpu player. Requires --human-port
+  --human-code <CODE>              Slippi connect code of the human player, e.g. ABCD#123
+  --human-name <NAME>              In-game name of the human player
+  -r, --skip-to-respawn            If the start frame is during a KO, export from the respawn instead of just before the KO
+  -sw, --swap-sheik-zelda          Swap Zelda and Sheik on the human player, working around a bug in UnclePunch before TM-CE v1.3
   -h, --help                       Print help
+
+Search options:
+  --human-char <CHAR>              Character of the human player, e.g. fox
+  --human-state <STATES>           Comma separated action states of the human player, e.g. CliffWait,CliffCatch
+  --human-min-percent <PERCENT>
+  --human-max-percent <PERCENT>
+  --human-ledge <DIST>             Maximum distance of the human player from a ledge
+  --human-airborne, --human-grounded
+  --cpu-*                          The same options for the cpu player
+  --min-gap <FRAMES>               Minimum frames between matches in a game [default: 300]
+  -n, --num-frames <NUM_FRAMES>    Number of frames to record [default: 360]
+  -m, --name <NAME>                Name to give to the recordings [default: search]
+  -o, --output-dir <DIR>           Directory for the exported recordings [default: .]
+  --list                           Only list matches, without exporting
+  --json                           Print matches as json
+
+Marker options:
+  --combo <BUTTONS>                '+' separated buttons to mark a moment with, e.g. l+dpad_left [default: dpad_down]
+                                     buttons: dpad_left dpad_right dpad_down dpad_up z r l a b x y start
+  --hold <FRAMES>                  Frames the combo must be held for [default: 1]
+  --before <FRAMES>                Frames to export before each marker [default: 300]
+  --after <FRAMES>                 Frames to export after each marker [default: 60]
+  -m, --name <NAME>                Name to give to the recordings [default: marker]
+  -o, --output-dir <DIR>           Directory for the exported recordings [default: .]
+  --json                           Print exports as json
+
+Tail options:
+  Exports the end of a game that is still being played. Given a directory, uses its newest slp file.
+  --seconds <SECONDS>              Seconds to export from the end of the game [default: 10]
+  -n, --num-frames <NUM_FRAMES>    Frames to export from the end of the game
+  --human <PLAYER>                 low, high, ports:H:C, code:ABCD#123 or name:NAME [default: low]
+  -m, --name <NAME>                Name to give to the recording [default: tail]
+  -o, --gci-dir <DIR>              Dolphin GCI folder to write to, e.g. 'User/GC/USA/Card A' [default: .]
+
+Punish options:
+  Exports each punish with the attacker as the human player, named e.g. 'punish 34% 1:22'.
+  --before <FRAMES>                Frames to export before the opening hit [default: 60]
+  --after <FRAMES>                 Frames to export after the punish ends [default: 30]
+  --min-damage <PERCENT>           Skip punishes dealing less damage [default: 0]
+  --min-hits <HITS>                Skip punishes with fewer hits [default: 1]
+  --kills-only                     Only export punishes that take a stock
+  --as-defender                    Export the defender as the human player
+  --timer-start <MM:SS>            Starting time of the game timer, for names [default: 8:00]
+  -o, --output-dir <DIR>           Directory for the exported recordings [default: .]
+  --list                           Only list punishes, without exporting
+  --json                           Print punishes as json
+
+Exportable options:
+  Lists the ranges of frames a recording can start on, and why the others can't.
+  Ranges are end exclusive.
+  --human <PLAYER>                 low, high, ports:H:C, code:ABCD#123 or name:NAME [default: low]
+  --json                           Print ranges as json
 ";
 
 fn parse_str(args: &[String], i: &mut usize) -> Result<String, String> {
@@ -56,22 +135,51 @@ fn run() -> Result<(), String> {
         return Ok(());
     }
 
+    if args[1] == "inspect" { return run_inspect(&args[2..]); }
+    if args[1] == "batch" { return batch::run(&args[2..]); }
+    if args[1] == "search" { return search_cmd::run(&args[2..]); }
+    if args[1] == "markers" { return markers_cmd::run(&args[2..]); }
+    if args[1] == "tail" { return tail_cmd::run(&args[2..]); }
+    if args[1] == "punishes" { return punishes_cmd::run(&args[2..]); }
+    if args[1] == "exportable" { return exportable_cmd::run(&args[2..]); }
+
     let mut file = None;
     let mut start_frame = None;
+    let mut timer_start = DEFAULT_TIMER_SECONDS;
     let mut num_frames = 360;
     let mut output_file = String::from("new_recording.gci");
     let mut name = String::from("new_recording");
     let mut flags = 0;
+    let mut human_port = None;
+    let mut cpu_port = None;
+    let mut human_code = None;
+    let mut human_name = None;
 
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
             "-s" | "--slp-file" => file = Some(parse_str(&args, &mut i)?),
-            "-f" | "--start-frame" => start_frame = Some(parse_num(&args, &mut i)?),
+            "-f" | "--start-frame" => start_frame = Some(parse_str(&args, &mut i)?),
+            "--timer-start" => {
+                let timer = parse_str(&args, &mut i)?;
+                timer_start = FrameAddress::parse_timer_start(&timer)
+                    .ok_or_else(|| format!("Error: '{}' is not a valid timer, expected MM:SS", timer))?;
+            }
             "-n" | "--num-frames" => num_frames = parse_num(&args, &mut i)?,
             "-o" | "--output-file" => output_file = parse_str(&args, &mut i)?,
             "-m" | "--name" => name = parse_str(&args, &mut i)?,
-            "-sw" | "--swap-sheik-zelda" => flags |= tm_replay::replay_flags::SWAP_SHEIK_ZELDA,
+            "--human-port" => human_port = Some(parse_num(&args, &mut i)?),
+            "--cpu-port" => cpu_port = Some(parse_num(&args, &mut i)?),
+            "--human-code" => human_code = Some(parse_str(&args, &mut i)?),
+            "--human-name" => human_name = Some(parse_str(&args, &mut i)?),
+            "-sw" | "--swap-sheik-zelda" => {
+                flags |= tm_replay::replay_flags::SWAP_SHEIK_ZELDA;
+                i += 1;
+            }
+            "-r" | "--skip-to-respawn" => {
+                flags |= tm_replay::replay_flags::SKIP_TO_RESPAWN;
+                i += 1;
+            }
             "-h" | "--help" => {
                 print!("{}", USAGE);
                 return Ok(());
@@ -86,62 +194,114 @@ fn run() -> Result<(), String> {
     };
 
     let start_frame = match start_frame {
-        Some(s) => s,
+        Some(s) => FrameAddress::parse(&s, timer_start)
+            .ok_or_else(|| format!("Error: '{}' is not a valid start frame", s))?,
         None => return Err("Error: '--start-frame' argument is required".into()),
     };
 
+    let human = match (human_port, cpu_port) {
+        _ if human_code.is_some() && human_name.is_some() => {
+            return Err("Error: only one of '--human-code' and '--human-name' may be given".into())
+        }
+        (None, None) => match (human_code, human_name) {
+            (Some(code), _) => HumanPort::connect_code(&code)
+                .ok_or_else(|| format!("Error: '{}' is not a valid connect code", code))?,
+            (_, Some(name)) => HumanPort::name(&name)
+                .ok_or_else(|| format!("Error: '{}' is not a valid name (max 31 ASCII characters)", name))?,
+            (None, None) => HumanPort::HumanLowPort,
+        },
+        _ if human_code.is_some() || human_name.is_some() => {
+            return Err("Error: ports cannot be given with '--human-code' or '--human-name'".into())
+        }
+        (Some(human), Some(cpu)) if (1..=4).contains(&human) && (1..=4).contains(&cpu) => {
+            HumanPort::Ports { human: human - 1, cpu: cpu - 1 }
+        }
+        (Some(_), Some(_)) => return Err("Error: ports must be between 1 and 4".into()),
+        _ => return Err("Error: '--human-port' and '--cpu-port' must be given together".into()),
+    };
+
     let game = match slp_parser::read_game(std::path::Path::new(&file)) {
         Ok(game) => game,
         Err(e) => return Err(format!("Error: failed to parse slp file: {}", e).into()),
     };
 
-    match construct_tm_replay_from_slp(&game, HumanPort::HumanLowPort, start_frame, num_frames, &name, flags) {
-        Ok(savestate) => {
+    let start_frame = start_frame.resolve(&game).map_err(error_message)?;
+
+    match construct_tm_replay_from_slp(&game, human, start_frame, num_frames, &name, flags) {
+        Ok((savestate, export_report)) => {
             std::fs::write(&output_file, &savestate)
                 .map_err(|e| format!("Could not write output file '{}': {}", &output_file, e))?;
             println!("Savestate file '{}' created", &output_file);
+            report::print(&export_report);
             Ok(())
         }
-        Err(e) => match e {
-            ReplayCreationError::NotTwoPlayerGame => {
-                return Err("Exports are only allowed in 1v1 replays.".into());
-            }
-            ReplayCreationError::RecordingOutOfBounds => {
-                return Err("The specified frame range is out of bounds".into());
-            }
-            ReplayCreationError::DurationTooLong => {
-                return Err(
-                    "Error: The duration exceeds the maximum allowed length (3600 frames)".into(),
-                );
-            }
-            ReplayCreationError::NoGoodExportFrame => {
-                return Err(
-                    "Error: Could not find a good export frame.".into(),
-                );
-            }
-            ReplayCreationError::FilenameTooLong => {
-                return Err(
-                    "Error: The provided name is too long (max 31 ASCII characters)".into(),
-                );
-            }
-            ReplayCreationError::FilenameNotASCII => {
-                return Err("Error: The provided name contains non-ASCII characters".into());
-            }
-            ReplayCreationError::SpecialActionState => {
-                return Err(
-                    "Error: The character is in a special action state, which is not supported"
-                        .into(),
-                );
-            }
-            ReplayCreationError::ZeldaOnCpu => {
-                return Err("Error: Zelda as CPU is not supported due to a known bug".into());
-            }
-            ReplayCreationError::OutdatedReplay => {
-                return Err(format!(
-                    "Error: Replay is out of date. Minimum slp version is {}.{}.0",
-                    MIN_VERSION_MAJOR, MIN_VERSION_MINOR
-                ));
+        Err(e) => Err(error_message(e)),
+    }
+}
+
+fn error_message(e: ReplayCreationError) -> String {
+    match e {
+        ReplayCreationError::NotTwoPlayerGame => {
+            "Exports from games without exactly two players require --human-port and --cpu-port.".into()
+        }
+        ReplayCreationError::PlayerNotFound => {
+            "Error: The given player is not in this replay".into()
+        }
+        ReplayCreationError::RecordingOutOfBounds => {
+            "The specified frame range is out of bounds".into()
+        }
+        ReplayCreationError::DurationTooLong => {
+            "Error: The duration exceeds the maximum allowed length (3600 frames)".into()
+        }
+        ReplayCreationError::NoGoodExportFrame => {
+            "Error: Could not find a good export frame.".into()
+        }
+        ReplayCreationError::FilenameTooLong => {
+            "Error: The provided name is too long (max 31 ASCII characters)".into()
+        }
+        ReplayCreationError::FilenameNotASCII => {
+            "Error: The provided name contains non-ASCII characters".into()
+        }
+        ReplayCreationError::SpecialActionState => {
+            "Error: The character is in a special action state, which is not supported".into()
+        }
+        ReplayCreationError::ZeldaOnCpu => {
+            "Error: Zelda as CPU is not supported due to a known bug".into()
+        }
+        ReplayCreationError::OutdatedReplay => {
+            format!(
+                "Error: Replay is out of date. Minimum slp version is {}.{}.0",
+                MIN_VERSION_MAJOR, MIN_VERSION_MINOR
+            )
+        }
+    }
+}
+
+fn run_inspect(args: &[String]) -> Result<(), String> {
+    let mut json = false;
+    let mut file = None;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return Ok(());
             }
-        },
+            f if file.is_none() && !f.starts_with('-') => file = Some(f),
+            err => return Err(format!("Error: Unknown argument '{}'", err)),
+        }
+    }
+
+    let file = file.ok_or("Error: 'inspect' requires a gci file")?;
+    let mut bytes = std::fs::read(file)
+        .map_err(|e| format!("Error: Could not read file '{}': {}", file, e))?;
+    let replay = inspect_tm_replay(&mut bytes)
+        .ok_or_else(|| format!("Error: '{}' is not a valid TM replay", file))?;
+
+    if json {
+        println!("{}", inspect::to_json(&replay));
+    } else {
+        inspect::print_text(&replay);
     }
+    Ok(())
 }
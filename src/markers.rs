@@ -0,0 +1,121 @@
+// Markers are button combinations players press during a game to flag a moment to export later.
+//
+// The D-pad does nothing in a match besides up taunting, so D-pad down, left and right are free to use.
+
+use slp_parser::{Frame, Game};
+use slp_parser::buttons_mask as button;
+use crate::HumanPort;
+use crate::search::Situation;
+
+pub const BUTTON_NAMES: &[(&str, u16)] = &[
+    ("dpad_left",  button::D_PAD_LEFT),
+    ("dpad_right", button::D_PAD_RIGHT),
+    ("dpad_down",  button::D_PAD_DOWN),
+    ("dpad_up",    button::D_PAD_UP),
+    ("z",          button::Z),
+    ("r",          button::R_DIGITAL),
+    ("l",          button::L_DIGITAL),
+    ("a",          button::A),
+    ("b",          button::B),
+    ("x",          button::X),
+    ("y",          button::Y),
+    ("start",      button::START),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Marker {
+    /// slp buttons mask. Every button must be held, other buttons are ignored.
+    pub buttons: u16,
+    /// Number of frames the buttons must be held for.
+    pub hold_frames: usize,
+}
+
+impl Default for Marker {
+    fn default() -> Self {
+        Marker { buttons: button::D_PAD_DOWN, hold_frames: 1 }
+    }
+}
+
+impl Marker {
+    /// Parses a '+' separated combo of names in `BUTTON_NAMES`, e.g. "dpad_down" or "l+r+dpad_left".
+    /// Returns None if a button is unknown.
+    pub fn parse(combo: &str, hold_frames: usize) -> Option<Marker> {
+        let mut buttons = 0;
+        for name in combo.split('+') {
+            let name = name.trim().to_ascii_lowercase();
+            let (_, mask) = BUTTON_NAMES.iter().find(|(n, _)| *n == name)?;
+            buttons |= mask;
+        }
+        Some(Marker { buttons, hold_frames: hold_frames.max(1) })
+    }
+
+    fn held(self, f: &Frame) -> bool {
+        f.buttons_mask & self.buttons == self.buttons
+    }
+}
+
+/// Returns the indices of the frames where the marker has been held for `hold_frames`.
+/// Continuing to hold the marker does not mark again.
+pub fn find_markers(frames: &[Frame], marker: Marker) -> Vec<usize> {
+    let mut markers = Vec::new();
+    let mut held_for = 0;
+    for (i, f) in frames.iter().enumerate() {
+        if marker.held(f) {
+            held_for += 1;
+            if held_for == marker.hold_frames { markers.push(i); }
+        } else {
+            held_for = 0;
+        }
+    }
+    markers
+}
+
+/// Finds markers pressed by either player in a 1v1 game. The player who pressed the marker is the human.
+/// Games that are not 1v1 have no markers.
+pub fn find_game_markers(game: &Game, marker: Marker) -> Vec<Situation> {
+    let (low, high) = match game.info.low_high_ports() {
+        Some(p) => p,
+        None => return Vec::new(),
+    };
+
+    let mut situations = Vec::new();
+    for (port, human) in [(low, HumanPort::HumanLowPort), (high, HumanPort::HumanHighPort)] {
+        let frames = game.frames[port].as_ref().unwrap();
+        situations.extend(find_markers(frames, marker).into_iter().map(|frame| Situation { frame, human }));
+    }
+
+    situations.sort_by_key(|s| s.frame);
+    situations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(buttons: &[u16]) -> Vec<Frame> {
+        buttons.iter().map(|&buttons_mask| Frame { buttons_mask, ..Frame::NULL }).collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Marker::parse("dpad_down", 1), Some(Marker::default()));
+        assert_eq!(Marker::parse(" L + r+DPAD_LEFT", 0), Some(Marker { buttons: 0x0061, hold_frames: 1 }));
+        assert_eq!(Marker::parse("dpad_down+c", 1), None);
+        assert_eq!(Marker::parse("", 1), None);
+    }
+
+    #[test]
+    fn find() {
+        let marker = Marker { buttons: 0x0004, hold_frames: 1 };
+        // other buttons held with the marker are ignored, and holding it only marks once
+        assert_eq!(find_markers(&frames(&[0, 0x0004, 0x0104, 0x0004, 0, 0x0004]), marker), vec![1, 5]);
+        assert!(find_markers(&frames(&[0x0001, 0x0100]), marker).is_empty());
+    }
+
+    #[test]
+    fn find_held() {
+        let marker = Marker { buttons: 0x0061, hold_frames: 3 };
+        let f = frames(&[0x0061, 0x0061, 0x0060, 0x0061, 0x0061, 0x0061, 0x0061, 0]);
+        assert_eq!(find_markers(&f, marker), vec![5]);
+    }
+}
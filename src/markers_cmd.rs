@@ -0,0 +1,89 @@
+use tm_replay::*;
+use tm_replay::markers::Marker;
+use crate::json::{Value, obj};
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut combo = String::from("dpad_down");
+    let mut hold_frames = 1;
+    let mut before = 300;
+    let mut after = 60;
+    let mut name = String::from("marker");
+    let mut output_dir = String::from(".");
+    let mut json = false;
+    let mut files = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--combo" => combo = crate::parse_str(args, &mut i)?,
+            "--hold" => hold_frames = crate::parse_num(args, &mut i)?,
+            "--before" => before = crate::parse_num(args, &mut i)?,
+            "--after" => after = crate::parse_num(args, &mut i)?,
+            "-m" | "--name" => name = crate::parse_str(args, &mut i)?,
+            "-o" | "--output-dir" => output_dir = crate::parse_str(args, &mut i)?,
+            "--json" => {
+                json = true;
+                i += 1;
+            }
+            "-h" | "--help" => {
+                print!("{}", crate::USAGE);
+                return Ok(());
+            }
+            f if !f.starts_with('-') => {
+                files.push(f.to_string());
+                i += 1;
+            }
+            err => return Err(format!("Error: Unknown argument '{}'", err)),
+        }
+    }
+
+    if files.is_empty() { return Err("Error: 'markers' requires at least one slp file".into()) }
+    let marker = Marker::parse(&combo, hold_frames)
+        .ok_or_else(|| format!("Error: '{}' is not a valid button combo", combo))?;
+
+    let output_dir = std::path::Path::new(&output_dir);
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Error: Could not create directory '{}': {}", output_dir.display(), e))?;
+
+    let mut results = Vec::new();
+    for file in files.iter() {
+        let game = slp_parser::read_game(std::path::Path::new(file))
+            .map_err(|e| format!("Error: failed to parse slp file '{}': {}", file, e))?;
+
+        for situation in markers::find_game_markers(&game, marker) {
+            let port = if situation.human == HumanPort::HumanLowPort { "low" } else { "high" };
+            let address = FrameAddress::SlippiFrame(situation.frame as i32 + FIRST_SLIPPI_FRAME);
+
+            // The window is cut short at the start of the game, and to the longest recording TM allows.
+            let start = situation.frame.saturating_sub(before);
+            let duration = (situation.frame - start + after).min(3600);
+
+            let output = construct_tm_replay_from_slp(&game, situation.human, start, duration, &name, 0)
+                .map_err(crate::error_message)
+                .and_then(|(savestate, _)| crate::batch::write_gci(output_dir, RecordingTime::from_slp(&game), &savestate));
+
+            if !json {
+                match &output {
+                    Ok(path) => println!("{} marker at {} port {} -> {}", file, address, port, path),
+                    Err(e) => println!("{} marker at {} port {}: {}", file, address, port, e),
+                }
+            }
+            results.push((file.as_str(), address, port, output));
+        }
+    }
+
+    if json {
+        println!("{}", Value::Arr(results.iter().map(|(file, address, port, output)| obj([
+            ("slp", (*file).into()),
+            ("marker_frame", address.to_string().into()),
+            ("human", (*port).into()),
+            ("output", output.as_ref().ok().map(String::as_str).into()),
+            ("error", output.as_ref().err().map(String::as_str).into()),
+        ])).collect()));
+    } else {
+        println!();
+        println!("{} markers found in {} files", results.len(), files.len());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+// Reading slp files that Slippi is still writing to.
+//
+// An slp is a ubjson object: {"raw": [event bytes], "metadata": {...}}.
+// While the game is running, the raw array length is zero, there is no game end event or metadata,
+// and the last event is usually cut off partway.
+// We cut the events after the last complete frame and finish the file so it can be parsed as normal.
+//
+// https://github.com/project-slippi/slippi-wiki/blob/master/SPEC.md
+
+use crate::RecordingTime;
+
+const RAW_HEADER: &[u8] = b"{U\x03raw[$U#l";
+const RAW_START: usize = RAW_HEADER.len() + 4;
+
+const EVENT_PAYLOADS: u8 = 0x35;
+const GAME_END: u8 = 0x39;
+const FRAME_BOOKEND: u8 = 0x3C;
+
+/// Returns a complete slp file containing every complete frame of an in-progress slp file.
+/// Complete slp files are returned unchanged.
+///
+/// `start_time` is written to the metadata, as it is only known once the game ends.
+///
+/// Returns None if the file is not an slp or no frame has been completed yet.
+pub fn complete_partial_slp(slp: &[u8], start_time: RecordingTime) -> Option<Vec<u8>> {
+    if slp.len() < RAW_START || &slp[..RAW_HEADER.len()] != RAW_HEADER { return None }
+
+    let raw_len = u32::from_be_bytes(slp[RAW_HEADER.len()..RAW_START].try_into().unwrap());
+    if raw_len != 0 { return Some(slp.to_vec()) }
+
+    let raw = &slp[RAW_START..];
+    if raw.len() < 2 || raw[0] != EVENT_PAYLOADS { return None }
+
+    // The payload size counts its own byte, then each event is (command u8, size u16).
+    let payloads_len = raw[1] as usize;
+    let payloads = raw.get(2..1+payloads_len)?;
+    let mut payload_sizes = [None; 256];
+    payload_sizes[EVENT_PAYLOADS as usize] = Some(payloads_len);
+    for p in payloads.chunks_exact(3) {
+        payload_sizes[p[0] as usize] = Some(u16::from_be_bytes([p[1], p[2]]) as usize);
+    }
+
+    let mut i = 0;
+    let mut last_bookend = None;
+    while i < raw.len() {
+        let command = raw[i];
+        let size = payload_sizes[command as usize]?;
+        let end = i + 1 + size;
+        if end > raw.len() { break } // cut off partway
+        if command == FRAME_BOOKEND { last_bookend = Some((i, end)) }
+        i = end;
+    }
+
+    let (bookend_start, events_end) = last_bookend?;
+    let last_frame = i32::from_be_bytes(raw.get(bookend_start+1..bookend_start+5)?.try_into().unwrap());
+
+    let game_end_size = payload_sizes[GAME_END as usize]?;
+    let mut game_end = vec![0u8; 1 + game_end_size];
+    game_end[0] = GAME_END;
+    if game_end_size >= 1 { game_end[1] = 7; }    // end method: no contest
+    if game_end_size >= 2 { game_end[2] = 0xFF; } // no LRAS initiator
+
+    let mut out = Vec::with_capacity(RAW_START + events_end + game_end.len() + 64);
+    out.extend_from_slice(RAW_HEADER);
+    out.extend_from_slice(&((events_end + game_end.len()) as u32).to_be_bytes());
+    out.extend_from_slice(&raw[..events_end]);
+    out.extend_from_slice(&game_end);
+
+    let start_at = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        start_time.year, start_time.month, start_time.day,
+        start_time.hour, start_time.minute, start_time.second,
+    );
+    out.extend_from_slice(b"U\x08metadata{");
+    out.extend_from_slice(b"U\x07startAtSU");
+    out.push(start_at.len() as u8);
+    out.extend_from_slice(start_at.as_bytes());
+    out.extend_from_slice(b"U\x09lastFramel");
+    out.extend_from_slice(&last_frame.to_be_bytes());
+    out.extend_from_slice(b"}}");
+
+    Some(out)
+}
+
+/// Index of the first frame of the last `num_frames` frames of a game.
+pub fn tail_start_frame(game: &slp_parser::Game, num_frames: usize) -> usize {
+    let game_len = game.frames.iter().flatten().map(|f| f.len()).max().unwrap_or(0);
+    game_len.saturating_sub(num_frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIME: RecordingTime = RecordingTime { month: 3, day: 9, year: 2024, hour: 13, minute: 5, second: 0 };
+
+    // Event payloads for a 2 byte 0x36 event, frame bookends with just the frame number, and a 2 byte game end.
+    fn raw_events() -> Vec<u8> {
+        let mut raw = vec![EVENT_PAYLOADS, 10, 0x36, 0, 2, FRAME_BOOKEND, 0, 4, GAME_END, 0, 2];
+        raw.extend_from_slice(&[0x36, 1, 2]);
+        raw.push(FRAME_BOOKEND);
+        raw.extend_from_slice(&(-123i32).to_be_bytes());
+        raw.extend_from_slice(&[0x36, 3, 4]);
+        raw.push(FRAME_BOOKEND);
+        raw.extend_from_slice(&(-122i32).to_be_bytes());
+        raw
+    }
+
+    fn slp(raw_len: u32, raw: &[u8]) -> Vec<u8> {
+        let mut slp = RAW_HEADER.to_vec();
+        slp.extend_from_slice(&raw_len.to_be_bytes());
+        slp.extend_from_slice(raw);
+        slp
+    }
+
+    #[test]
+    fn cut_after_last_frame() {
+        let raw = raw_events();
+        let mut partial = raw.clone();
+        partial.extend_from_slice(&[0x36, 5]); // cut off partway
+
+        let out = complete_partial_slp(&slp(0, &partial), TIME).unwrap();
+
+        let mut expected_raw = raw.clone();
+        expected_raw.extend_from_slice(&[GAME_END, 7, 0xFF]);
+        let mut expected = slp(expected_raw.len() as u32, &expected_raw);
+        expected.extend_from_slice(b"U\x08metadata{U\x07startAtSU\x142024-03-09T13:05:00ZU\x09lastFramel");
+        expected.extend_from_slice(&(-122i32).to_be_bytes());
+        expected.extend_from_slice(b"}}");
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn complete_unchanged() {
+        let complete = slp(5, &[1, 2, 3, 4, 5]);
+        assert_eq!(complete_partial_slp(&complete, TIME), Some(complete));
+    }
+
+    #[test]
+    fn no_frame_yet() {
+        let raw = raw_events();
+        assert!(complete_partial_slp(&slp(0, &raw[..14]), TIME).is_none());
+        assert!(complete_partial_slp(&slp(0, &raw[..12]), TIME).is_none());
+        assert!(complete_partial_slp(b"not an slp", TIME).is_none());
+    }
+}
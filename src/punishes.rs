@@ -0,0 +1,190 @@
+// Finding punishes: everything from an opening to the defender getting out or dying.
+//
+// Similar to slippi-js's conversions. A punish starts when the defender takes damage,
+// and ends when they lose a stock or have been out of hitstun, grabs and knockdowns
+// for PUNISH_RESET_FRAMES frames without being hit again.
+
+use slp_parser::{ActionState, Frame, Game};
+use crate::{HumanPort, FIRST_SLIPPI_FRAME};
+
+pub const PUNISH_RESET_FRAMES: usize = 45;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Punish {
+    /// The player dealing the punish. Always HumanLowPort or HumanHighPort.
+    pub attacker: HumanPort,
+    /// Index into the slp frames of the opening hit.
+    pub start: usize,
+    /// Index into the slp frames of the last hit or the stock loss.
+    pub end: usize,
+    pub start_percent: f32,
+    pub end_percent: f32,
+    pub hits: u32,
+    pub killed: bool,
+}
+
+impl Punish {
+    pub fn damage(&self) -> f32 {
+        self.end_percent - self.start_percent
+    }
+
+    /// e.g. "punish 34% 1:22", with the game timer at the opening.
+    pub fn name(&self, timer_start: u32) -> String {
+        let elapsed = (self.start as i64 + FIRST_SLIPPI_FRAME as i64).max(0) as u32 / 60;
+        let timer = timer_start.saturating_sub(elapsed);
+        format!("punish {}% {}:{:02}", self.damage().round() as u32, timer / 60, timer % 60)
+    }
+}
+
+// Whether the defender is still stuck in the punish.
+fn punished(f: &Frame) -> bool {
+    use slp_parser::StandardActionState::*;
+
+    if f.hitlag_frames != 0.0 || crate::is_grabbed(f.state) { return true }
+    matches!(f.state, ActionState::Standard(
+        DamageHi1 | DamageHi2 | DamageHi3 | DamageN1 | DamageN2 | DamageN3
+            | DamageLw1 | DamageLw2 | DamageLw3 | DamageAir1 | DamageAir2 | DamageAir3
+            | DamageFlyHi | DamageFlyN | DamageFlyLw | DamageFlyTop | DamageFlyRoll | DamageFall
+            | DownBoundU | DownWaitU | DownDamageU | DownBoundD | DownWaitD | DownDamageD
+            | Passive | PassiveStandF | PassiveStandB | PassiveWall | PassiveWallJump | PassiveCeil
+    ))
+}
+
+/// Finds the punishes dealt on `defender` frames by the player on `attacker_port`.
+/// Damage from anything else, such as the stage or the defender's own items, is not counted as a hit.
+pub fn find_punishes_on(defender: &[Frame], attacker: HumanPort, attacker_port: u8) -> Vec<Punish> {
+    let mut punishes = Vec::new();
+    let mut current: Option<Punish> = None;
+    let mut frames_out = 0;
+
+    for i in 1..defender.len() {
+        let (prev, f) = (&defender[i-1], &defender[i]);
+
+        if f.stock_count < prev.stock_count {
+            if let Some(mut p) = current.take() {
+                p.end = i;
+                p.killed = true;
+                punishes.push(p);
+            }
+            continue;
+        }
+
+        if f.percent > prev.percent && f.last_hit_by == attacker_port {
+            let p = current.get_or_insert(Punish {
+                attacker,
+                start: i,
+                end: i,
+                start_percent: prev.percent,
+                end_percent: prev.percent,
+                hits: 0,
+                killed: false,
+            });
+            p.end = i;
+            p.end_percent = f.percent;
+            p.hits += 1;
+            frames_out = 0;
+        } else if current.is_some() {
+            if punished(f) {
+                frames_out = 0;
+            } else {
+                frames_out += 1;
+                if frames_out >= PUNISH_RESET_FRAMES {
+                    punishes.extend(current.take());
+                }
+            }
+        }
+    }
+
+    punishes.extend(current);
+    punishes
+}
+
+/// Finds the punishes both players deal in a 1v1 game, in order of their openings.
+/// Games that are not 1v1 have no punishes.
+pub fn find_punishes(game: &Game) -> Vec<Punish> {
+    let (low, high) = match game.info.low_high_ports() {
+        Some(p) => p,
+        None => return Vec::new(),
+    };
+
+    let mut punishes = find_punishes_on(game.frames[high].as_ref().unwrap(), HumanPort::HumanLowPort, low as u8);
+    punishes.extend(find_punishes_on(game.frames[low].as_ref().unwrap(), HumanPort::HumanHighPort, high as u8));
+    punishes.sort_by_key(|p| p.start);
+    punishes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATTACKER: u8 = 1;
+    const OTHER: u8 = 3;
+
+    // Standing, hit last by `last_hit_by`.
+    fn frame(percent: f32, last_hit_by: u8, stock_count: u8) -> Frame {
+        Frame {
+            state: ActionState::Standard(slp_parser::StandardActionState::Wait),
+            hitlag_frames: 0.0,
+            percent,
+            last_hit_by,
+            stock_count,
+            ..Frame::NULL
+        }
+    }
+
+    fn idle(frames: &mut Vec<Frame>, n: usize) {
+        let last = frames.last().unwrap();
+        let (percent, last_hit_by, stock_count) = (last.percent, last.last_hit_by, last.stock_count);
+        frames.extend((0..n).map(|_| frame(percent, last_hit_by, stock_count)));
+    }
+
+    #[test]
+    fn combo() {
+        let mut f = vec![frame(0.0, OTHER, 4), frame(10.0, ATTACKER, 4)];
+        idle(&mut f, 10);
+        f.push(frame(22.0, ATTACKER, 4));
+        idle(&mut f, PUNISH_RESET_FRAMES + 5);
+        f.push(frame(30.0, ATTACKER, 4));
+
+        let punishes = find_punishes_on(&f, HumanPort::HumanLowPort, ATTACKER);
+        assert_eq!(punishes.len(), 2);
+        assert_eq!((punishes[0].start, punishes[0].end, punishes[0].hits), (1, 12, 2));
+        assert_eq!(punishes[0].damage(), 22.0);
+        assert!(!punishes[0].killed);
+        assert_eq!((punishes[1].start, punishes[1].hits), (f.len() - 1, 1));
+        assert_eq!(punishes[1].start_percent, 22.0);
+    }
+
+    #[test]
+    fn kill() {
+        let mut f = vec![frame(50.0, OTHER, 4), frame(70.0, ATTACKER, 4)];
+        idle(&mut f, 20);
+        f.push(frame(0.0, ATTACKER, 3));
+
+        let punishes = find_punishes_on(&f, HumanPort::HumanHighPort, ATTACKER);
+        assert_eq!(punishes.len(), 1);
+        assert!(punishes[0].killed);
+        assert_eq!((punishes[0].end, punishes[0].damage()), (f.len() - 1, 20.0));
+    }
+
+    #[test]
+    fn other_damage_is_not_a_hit() {
+        let mut f = vec![frame(0.0, OTHER, 4), frame(10.0, OTHER, 4)];
+        idle(&mut f, 5);
+        assert!(find_punishes_on(&f, HumanPort::HumanLowPort, ATTACKER).is_empty());
+    }
+
+    #[test]
+    fn name() {
+        let punish = Punish {
+            attacker: HumanPort::HumanLowPort,
+            start: 60 * 60 * 2 + 37 * 60 + 123,
+            end: 0,
+            start_percent: 10.0,
+            end_percent: 43.6,
+            hits: 3,
+            killed: false,
+        };
+        assert_eq!(punish.name(480), "punish 34% 5:23");
+    }
+}
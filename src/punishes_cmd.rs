@@ -0,0 +1,126 @@
+use tm_replay::*;
+use crate::json::{Value, obj};
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut before = 60;
+    let mut after = 30;
+    let mut min_damage = 0.0;
+    let mut min_hits = 1;
+    let mut kills_only = false;
+    let mut as_defender = false;
+    let mut timer_start = DEFAULT_TIMER_SECONDS;
+    let mut output_dir = String::from(".");
+    let mut list_only = false;
+    let mut json = false;
+    let mut files = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--before" => before = crate::parse_num(args, &mut i)?,
+            "--after" => after = crate::parse_num(args, &mut i)?,
+            "--min-damage" => min_damage = crate::parse_num(args, &mut i)? as f32,
+            "--min-hits" => min_hits = crate::parse_num(args, &mut i)? as u32,
+            "--timer-start" => {
+                let timer = crate::parse_str(args, &mut i)?;
+                timer_start = FrameAddress::parse_timer_start(&timer)
+                    .ok_or_else(|| format!("Error: '{}' is not a valid timer, expected MM:SS", timer))?;
+            }
+            "-o" | "--output-dir" => output_dir = crate::parse_str(args, &mut i)?,
+            "--kills-only" => {
+                kills_only = true;
+                i += 1;
+            }
+            "--as-defender" => {
+                as_defender = true;
+                i += 1;
+            }
+            "--list" => {
+                list_only = true;
+                i += 1;
+            }
+            "--json" => {
+                json = true;
+                i += 1;
+            }
+            "-h" | "--help" => {
+                print!("{}", crate::USAGE);
+                return Ok(());
+            }
+            f if !f.starts_with('-') => {
+                files.push(f.to_string());
+                i += 1;
+            }
+            err => return Err(format!("Error: Unknown argument '{}'", err)),
+        }
+    }
+
+    if files.is_empty() { return Err("Error: 'punishes' requires at least one slp file".into()) }
+
+    let output_dir = std::path::Path::new(&output_dir);
+    if !list_only {
+        std::fs::create_dir_all(output_dir)
+            .map_err(|e| format!("Error: Could not create directory '{}': {}", output_dir.display(), e))?;
+    }
+
+    let mut results = Vec::new();
+    for file in files.iter() {
+        let game = slp_parser::read_game(std::path::Path::new(file))
+            .map_err(|e| format!("Error: failed to parse slp file '{}': {}", file, e))?;
+
+        let punishes = punishes::find_punishes(&game).into_iter()
+            .filter(|p| p.damage() >= min_damage && p.hits >= min_hits && (p.killed || !kills_only));
+
+        for punish in punishes {
+            let name = punish.name(timer_start);
+            let human = match (punish.attacker, as_defender) {
+                (HumanPort::HumanLowPort, false) | (HumanPort::HumanHighPort, true) => HumanPort::HumanLowPort,
+                _ => HumanPort::HumanHighPort,
+            };
+
+            let start = punish.start.saturating_sub(before);
+            let duration = (punish.end + after - start).min(3600);
+
+            let output = if list_only {
+                Ok(None)
+            } else {
+                construct_tm_replay_from_slp(&game, human, start, duration, &name, 0)
+                    .map_err(crate::error_message)
+                    .and_then(|(savestate, _)| crate::batch::write_gci(output_dir, RecordingTime::from_slp(&game), &savestate))
+                    .map(Some)
+            };
+
+            if !json {
+                let kill = if punish.killed { ", kill" } else { "" };
+                let summary = format!("{} '{}' ({} hits{})", file, name, punish.hits, kill);
+                match &output {
+                    Ok(None) => println!("{}", summary),
+                    Ok(Some(path)) => println!("{} -> {}", summary, path),
+                    Err(e) => println!("{}: {}", summary, e),
+                }
+            }
+            results.push((file.as_str(), name, punish, output));
+        }
+    }
+
+    if json {
+        println!("{}", Value::Arr(results.iter().map(|(file, name, punish, output)| obj([
+            ("slp", (*file).into()),
+            ("name", name.as_str().into()),
+            ("attacker", if punish.attacker == HumanPort::HumanLowPort { "low" } else { "high" }.into()),
+            ("start_frame", FrameAddress::SlippiFrame(punish.start as i32 + FIRST_SLIPPI_FRAME).to_string().into()),
+            ("end_frame", FrameAddress::SlippiFrame(punish.end as i32 + FIRST_SLIPPI_FRAME).to_string().into()),
+            ("start_percent", punish.start_percent.into()),
+            ("end_percent", punish.end_percent.into()),
+            ("hits", punish.hits.into()),
+            ("killed", punish.killed.into()),
+            ("output", output.as_ref().ok().and_then(|o| o.as_deref()).into()),
+            ("error", output.as_ref().err().map(String::as_str).into()),
+        ])).collect()));
+    } else {
+        println!();
+        println!("{} punishes found in {} files", results.len(), files.len());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use tm_replay::*;
+
+pub fn fighter_name(fighter: ExportFighter) -> &'static str {
+    match fighter {
+        ExportFighter::Human => "human",
+        ExportFighter::HumanFollower => "human follower",
+        ExportFighter::Cpu => "cpu",
+        ExportFighter::CpuFollower => "cpu follower",
+    }
+}
+
+pub fn reason_name(reason: Unexportable) -> &'static str {
+    match reason {
+        Unexportable::Entry => "entry",
+        Unexportable::Dead => "dead",
+        Unexportable::AttackHitbox => "attack hitbox",
+        Unexportable::Grab => "grab",
+        Unexportable::UnsupportedState => "unsupported state",
+        Unexportable::SpecialState => "special state",
+        Unexportable::PendingKnockback => "knockback not recorded during hitlag",
+    }
+}
+
+pub fn slippi_frame(idx: usize) -> String {
+    FrameAddress::SlippiFrame(idx as i32 + FIRST_SLIPPI_FRAME).to_string()
+}
+
+/// One line for each adjustment and approximation in the report.
+pub fn notes(report: &ExportReport) -> Vec<String> {
+    let adjustments = report.adjustments.iter().map(|a| match *a {
+        Adjustment::SkippedToRespawn { frames } => {
+            format!("start moved forward {} frames past a KO", frames)
+        }
+        Adjustment::MovedBack { frames, fighter, reason, frame } => {
+            format!(
+                "start moved back {} frames: {} on {} ({})",
+                frames, reason_name(reason), slippi_frame(frame), fighter_name(fighter),
+            )
+        }
+        Adjustment::SkippedEntry { frames } => {
+            format!("start moved forward {} frames past the entry animation", frames)
+        }
+        Adjustment::TruncatedToGameEnd { frames } => {
+            format!("recording cut short by {} frames at the end of the game", frames)
+        }
+    });
+
+    let approximations = report.approximations.iter().map(|a| match *a {
+        Approximation::GrabTimer(f) => format!("grab breakout timer estimated from percent, ignoring mashing ({})", fighter_name(f)),
+        Approximation::Knockback(f) => format!("knockback estimated from launch velocity, tumble and wiggle out flags not restored ({})", fighter_name(f)),
+        Approximation::FollowerDesynced(f) => format!("follower is desynced and its AI is not restored exactly ({})", fighter_name(f)),
+        Approximation::FollowerBuffer(f) => format!("follower's queued inputs are not restored ({})", fighter_name(f)),
+        Approximation::SpecialState(f) => format!("special state rebuilt from the state history ({})", fighter_name(f)),
+        Approximation::UncheckedSpecialState(f) => format!("special state exported without its unchecked state vars ({})", fighter_name(f)),
+        Approximation::Resources(f) => format!("stored charge or copied ability is not restored ({})", fighter_name(f)),
+        Approximation::Intangibility(f) => format!("intangibility from the current action is not restored ({})", fighter_name(f)),
+        Approximation::AirtimeSpecials(f) => format!("airdodge or aerial special used this airtime is not restored ({})", fighter_name(f)),
+        Approximation::Walljumps(f) => format!("walljumps counted from the state history ({})", fighter_name(f)),
+    });
+
+    adjustments.chain(approximations).collect()
+}
+
+pub fn print(report: &ExportReport) {
+    if report.frame != report.requested_frame || report.duration != report.requested_duration {
+        println!(
+            "Recording starts on {} for {} frames (requested {} for {} frames)",
+            slippi_frame(report.frame), report.duration,
+            slippi_frame(report.requested_frame), report.requested_duration,
+        );
+    }
+    for note in notes(report) {
+        println!("  note: {}", note);
+    }
+}
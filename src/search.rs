@@ -0,0 +1,130 @@
+// Finding situations in slp files to export as recordings.
+//
+// Only frames that construct_tm_replay_from_slp can export as-is are matched,
+// so exporting a match starts on the matched frame rather than searching back for a good one.
+
+use slp_parser::{ActionState, Frame, Game};
+use crate::{HumanPort, FIRST_SLIPPI_FRAME};
+
+/// Action state name without the enum wrappers, e.g. "Standard(CliffWait)" -> "CliffWait".
+pub fn action_state_name(state: ActionState) -> String {
+    let name = format!("{:?}", state);
+    name.trim_end_matches(')').rsplit('(').next().unwrap_or(&name).to_string()
+}
+
+/// Constraints on one fighter. Unset fields match anything.
+#[derive(Clone, Debug, Default)]
+pub struct FighterQuery {
+    pub character: Option<slp_parser::Character>,
+    /// Any of these action states, named as by `action_state_name`, e.g. "CliffWait" or "DownWaitU".
+    pub states: Vec<String>,
+    pub min_percent: Option<f32>,
+    pub max_percent: Option<f32>,
+    /// Maximum distance from the nearest ledge of the main platform.
+    /// Never matches on stages without ledge data.
+    pub max_ledge_distance: Option<f32>,
+    pub airborne: Option<bool>,
+}
+
+impl FighterQuery {
+    pub fn matches(&self, f: &Frame, stage: slp_parser::Stage) -> bool {
+        if let Some(c) = self.character { if f.character != c { return false } }
+        if let Some(p) = self.min_percent { if f.percent < p { return false } }
+        if let Some(p) = self.max_percent { if f.percent > p { return false } }
+        if let Some(a) = self.airborne { if f.is_airborne != a { return false } }
+
+        if let Some(max_dist) = self.max_ledge_distance {
+            let (ledge_x, ledge_y) = match crate::stage_data::ledge(stage.to_u16_external()) {
+                Some(ledge) => ledge,
+                None => return false,
+            };
+            let dx = f.position.x.abs() - ledge_x;
+            let dy = f.position.y - ledge_y;
+            if (dx * dx + dy * dy).sqrt() > max_dist { return false }
+        }
+
+        // checked last, as naming the state allocates
+        if !self.states.is_empty() {
+            let name = action_state_name(f.state);
+            if !self.states.iter().any(|s| *s == name) { return false }
+        }
+
+        true
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SituationQuery {
+    pub human: FighterQuery,
+    pub cpu: FighterQuery,
+    /// Each run of matching frames is found once, on its first frame.
+    /// Runs starting within this many frames of the last match with the same human are skipped.
+    pub min_gap: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Situation {
+    /// Index into the slp frames. Pass to construct_tm_replay_from_slp.
+    pub frame: usize,
+    /// Always HumanLowPort or HumanHighPort.
+    pub human: HumanPort,
+}
+
+/// Finds frames in a 1v1 game matching the query, trying each player as the human.
+/// Games that are not 1v1 have no matches.
+pub fn search_game(game: &Game, query: &SituationQuery) -> Vec<Situation> {
+    let stage = game.info.stage;
+    search_game_with(game, query.min_gap, |human, cpu| {
+        query.human.matches(human, stage) && query.cpu.matches(cpu, stage)
+    })
+}
+
+/// Like `search_game`, but with an arbitrary predicate on the human and cpu leader frames.
+pub fn search_game_with(
+    game: &Game,
+    min_gap: usize,
+    mut predicate: impl FnMut(&Frame, &Frame) -> bool,
+) -> Vec<Situation> {
+    let (low, high) = match game.info.low_high_ports() {
+        Some(p) => p,
+        None => return Vec::new(),
+    };
+
+    let low_frames = game.frames[low].as_ref().unwrap();
+    let high_frames = game.frames[high].as_ref().unwrap();
+    let mut fighters = vec![&low_frames[..], &high_frames[..]];
+    fighters.extend(game.follower_frames[low].as_ref().map(|f| &f[..]));
+    fighters.extend(game.follower_frames[high].as_ref().map(|f| &f[..]));
+
+    let len = fighters.iter().map(|f| f.len()).min().unwrap_or(0);
+    // The recording can't start before 'GO' - construct_tm_replay_from_slp skips entry anyway.
+    let start = (-FIRST_SLIPPI_FRAME) as usize;
+
+    let mut situations = Vec::new();
+    for (human, human_frames, cpu_frames) in [
+        (HumanPort::HumanLowPort, low_frames, high_frames),
+        (HumanPort::HumanHighPort, high_frames, low_frames),
+    ] {
+        let mut last_match: Option<usize> = None;
+        let mut prev_matched = false;
+        for i in start..len {
+            let matched = predicate(&human_frames[i], &cpu_frames[i])
+                && fighters.iter().all(|f| exportable(&f[i]));
+            let run_start = matched && !prev_matched;
+            prev_matched = matched;
+
+            if !run_start { continue }
+            if last_match.is_some_and(|last| i - last < min_gap) { continue }
+
+            situations.push(Situation { frame: i, human });
+            last_match = Some(i);
+        }
+    }
+
+    situations.sort_by_key(|s| s.frame);
+    situations
+}
+
+fn exportable(f: &Frame) -> bool {
+    crate::fighter_unexportable(f).is_none()
+}
@@ -0,0 +1,167 @@
+use tm_replay::*;
+use tm_replay::search::SituationQuery;
+use crate::json::{Value, obj};
+
+// Character names are matched without case, spaces or punctuation, e.g. "fox", "CaptainFalcon", "mr game & watch".
+fn parse_character(s: &str) -> Option<slp_parser::Character> {
+    let simplify = |s: &str| s.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>();
+
+    let s = simplify(s);
+    (0..=32u8)
+        .filter_map(slp_parser::Character::from_u8_external)
+        .find(|c| simplify(&format!("{:?}", c)) == s)
+}
+
+fn parse_f32(args: &[String], i: &mut usize) -> Result<f32, String> {
+    let s = crate::parse_str(args, i)?;
+    s.parse::<f32>().map_err(|_| format!("Error: '{}' is not a number", s))
+}
+
+fn find_slp_files(dir: &std::path::Path, files: &mut Vec<std::path::PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Error: Could not read directory '{}': {}", dir.display(), e))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Error: Could not read directory '{}': {}", dir.display(), e))?
+            .path();
+        if path.is_dir() {
+            find_slp_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("slp")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut query = SituationQuery { min_gap: 300, ..SituationQuery::default() };
+    let mut num_frames = 360;
+    let mut name = String::from("search");
+    let mut output_dir = String::from(".");
+    let mut list_only = false;
+    let mut json = false;
+    let mut dir = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+
+        // --human-* and --cpu-* options
+        let fighter_option = arg.strip_prefix("--human-").map(|o| (&mut query.human, o))
+            .or_else(|| arg.strip_prefix("--cpu-").map(|o| (&mut query.cpu, o)));
+        if let Some((fighter, option)) = fighter_option {
+            match option {
+                "char" => {
+                    let c = crate::parse_str(args, &mut i)?;
+                    fighter.character = Some(parse_character(&c)
+                        .ok_or_else(|| format!("Error: '{}' is not a character", c))?);
+                }
+                "state" => {
+                    let states = crate::parse_str(args, &mut i)?;
+                    fighter.states.extend(states.split(',').map(|s| s.trim().to_string()));
+                }
+                "min-percent" => fighter.min_percent = Some(parse_f32(args, &mut i)?),
+                "max-percent" => fighter.max_percent = Some(parse_f32(args, &mut i)?),
+                "ledge" => fighter.max_ledge_distance = Some(parse_f32(args, &mut i)?),
+                "airborne" => {
+                    fighter.airborne = Some(true);
+                    i += 1;
+                }
+                "grounded" => {
+                    fighter.airborne = Some(false);
+                    i += 1;
+                }
+                _ => return Err(format!("Error: Unknown argument '{}'", arg)),
+            }
+            continue;
+        }
+
+        match arg {
+            "--min-gap" => query.min_gap = crate::parse_num(args, &mut i)?,
+            "-n" | "--num-frames" => num_frames = crate::parse_num(args, &mut i)?,
+            "-m" | "--name" => name = crate::parse_str(args, &mut i)?,
+            "-o" | "--output-dir" => output_dir = crate::parse_str(args, &mut i)?,
+            "--list" => {
+                list_only = true;
+                i += 1;
+            }
+            "--json" => {
+                json = true;
+                i += 1;
+            }
+            "-h" | "--help" => {
+                print!("{}", crate::USAGE);
+                return Ok(());
+            }
+            d if dir.is_none() && !d.starts_with('-') => {
+                dir = Some(d.to_string());
+                i += 1;
+            }
+            err => return Err(format!("Error: Unknown argument '{}'", err)),
+        }
+    }
+
+    let dir = dir.ok_or("Error: 'search' requires a directory of slp files")?;
+    let mut files = Vec::new();
+    find_slp_files(std::path::Path::new(&dir), &mut files)?;
+    files.sort();
+
+    let output_dir = std::path::Path::new(&output_dir);
+    if !list_only {
+        std::fs::create_dir_all(output_dir)
+            .map_err(|e| format!("Error: Could not create directory '{}': {}", output_dir.display(), e))?;
+    }
+
+    let mut results = Vec::new();
+    for file in files.iter() {
+        let game = match slp_parser::read_game(file) {
+            Ok(game) => game,
+            Err(e) => {
+                if !json { eprintln!("skipped {}: failed to parse slp file: {}", file.display(), e); }
+                continue;
+            }
+        };
+
+        for situation in search::search_game(&game, &query) {
+            let port = if situation.human == HumanPort::HumanLowPort { "low" } else { "high" };
+            let address = FrameAddress::SlippiFrame(situation.frame as i32 + FIRST_SLIPPI_FRAME);
+
+            let output = if list_only {
+                Ok(None)
+            } else {
+                construct_tm_replay_from_slp(&game, situation.human, situation.frame, num_frames, &name, 0)
+                    .map_err(crate::error_message)
+                    .and_then(|(savestate, _)| crate::batch::write_gci(output_dir, RecordingTime::from_slp(&game), &savestate))
+                    .map(Some)
+            };
+
+            if !json {
+                match &output {
+                    Ok(None) => println!("{} {} port {}", file.display(), address, port),
+                    Ok(Some(path)) => println!("{} {} port {} -> {}", file.display(), address, port, path),
+                    Err(e) => println!("{} {} port {}: {}", file.display(), address, port, e),
+                }
+            }
+            results.push((file.display().to_string(), address, port, output));
+        }
+    }
+
+    if json {
+        println!("{}", Value::Arr(results.iter().map(|(file, address, port, output)| obj([
+            ("slp", file.as_str().into()),
+            ("start_frame", address.to_string().into()),
+            ("human", (*port).into()),
+            ("output", output.as_ref().ok().and_then(|o| o.as_deref()).into()),
+            ("error", output.as_ref().err().map(String::as_str).into()),
+        ])).collect()));
+    } else {
+        println!();
+        println!("{} situations found in {} files", results.len(), files.len());
+    }
+
+    Ok(())
+}
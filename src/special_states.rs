@@ -0,0 +1,166 @@
+// Reconstruction of special action states.
+//
+// Only the first char state var is recorded in slp files,
+// so anything else a special needs to play out correctly is rebuilt here from the surrounding frames.
+//
+// Special action state ids start at 341 and follow the special anim maps in examples/extract_fn_table.rs.
+// Specials not listed in SPECIAL_STATES are exported with only the recorded state var, as Unchecked.
+// Only list a state as Stateless or Reconstruct once its state var layout has been checked against the game.
+
+use slp_parser::Frame;
+
+pub struct SpecialVars {
+    pub char_fighter_var: [u8; 208],
+    pub char_state_var: [u8; 72],
+    pub subaction_flags: [u8; 16],
+}
+
+#[derive(Copy, Clone)]
+pub enum Support {
+    /// Nothing past the common fighter state needs to be written.
+    Stateless,
+    Reconstruct(fn(&[Frame], usize, &mut SpecialVars)),
+    /// The state vars past the first haven't been looked at.
+    /// Exported with only the recorded state var, and reported as `Approximation::UncheckedSpecialState`.
+    Unchecked,
+    /// Relies on state we cannot export, such as a captured opponent or a controlled projectile.
+    Unsupported,
+}
+
+use Support::*;
+
+const SPECIAL_STATE_START: u16 = 341;
+
+pub fn support(character: slp_parser::Character, state_num: u16) -> Support {
+    if state_num < SPECIAL_STATE_START { return Stateless }
+
+    SPECIAL_STATES[character as usize].iter()
+        .find(|(first, last, _)| (*first..=*last).contains(&state_num))
+        .map(|(_, _, support)| *support)
+        .unwrap_or(Unchecked)
+}
+
+// indexed by character, then searched for the range containing the state id.
+// (first state id, last state id (inclusive), support)
+pub const SPECIAL_STATES: &[&[(u16, u16, Support)]] = &[
+    // Mario
+    &[],
+    // Fox
+    &[],
+    // Captain Falcon
+    &[
+        (355, 355, Unsupported), // FalconDiveCatch
+    ],
+    // Donkey Kong
+    &[
+        (351, 368, Unsupported), // KongKarryWait..KongKarryAirThrowDown
+    ],
+    // Kirby
+    &[
+        (341, 344, Reconstruct(multijump)), // Jump2..Jump5
+        (356, 370, Unsupported),            // SwallowGroundCapture..Unknown370
+        (374, 382, Unsupported),            // SwallowAirCapture..SwallowAirCaptureTurn
+        (414, 417, Unsupported),            // YoshiEggLayGroundCaptureStart..Unknown417
+        (419, 422, Unsupported),            // YoshiEggLayAirCaptureStart..Unknown422
+    ],
+    // Bowser
+    &[
+        (348, 352, Unsupported), // KoopaKlawGroundGrab..KoopaKlawGroundThrowB
+        (354, 358, Unsupported), // KoopaKlawAirGrab..KoopaKlawAirThrowB
+    ],
+    // Link
+    &[],
+    // Sheik
+    &[
+        (361, 364, Unsupported), // TransformGround..TransformAirEnding
+    ],
+    // Ness
+    &[
+        (359, 359, Unsupported), // PKThunderGround
+        (363, 363, Unsupported), // PKThunderAir
+    ],
+    // Peach
+    &[
+        (341, 341, Reconstruct(float)), // Float
+        (344, 348, Reconstruct(float)), // FloatNair..FloatDair
+    ],
+    // Popo
+    &[
+        (347, 356, Unsupported), // PopoBelayGroundStartup..PopoBelayAirFailedCatapultingEnd
+        (361, 365, Unsupported), // NanaBelayCatapultStartup..NanaBelayCatapulting
+    ],
+    // Nana
+    &[
+        (347, 356, Unsupported), // PopoBelayGroundStartup..PopoBelayAirFailedCatapultingEnd
+        (361, 365, Unsupported), // NanaBelayCatapultStartup..NanaBelayCatapulting
+    ],
+    // Pikachu
+    &[],
+    // Samus
+    &[],
+    // Yoshi
+    &[
+        (347, 350, Unsupported), // EggLayGroundCaptureStart..Unknown350
+        (352, 355, Unsupported), // EggLayAirCaptureStart..Unknown
+    ],
+    // Jigglypuff
+    &[
+        (341, 344, Reconstruct(multijump)), // Jump2..Jump5
+    ],
+    // Mewtwo
+    &[],
+    // Luigi
+    &[],
+    // Marth
+    &[
+        (367, 368, Reconstruct(dolphin_slash)), // DolphinSlashGround, DolphinSlashAir
+    ],
+    // Zelda
+    &[
+        (355, 358, Unsupported), // TransformGround..TransformAirEnding
+    ],
+    // Young Link
+    &[],
+    // Dr. Mario
+    &[],
+    // Falco
+    &[],
+    // Pichu
+    &[],
+    // Mr. Game & Watch
+    &[],
+    // Ganondorf
+    &[
+        (355, 355, Unsupported), // DarkDiveCatch
+    ],
+    // Roy
+    &[
+        (367, 368, Reconstruct(blazer)), // BlazerGround, BlazerAir
+    ],
+];
+
+fn multijump(frames: &[Frame], frame_idx: usize, vars: &mut SpecialVars) {
+    vars.subaction_flags[3] = if frames[frame_idx].anim_frame >= 28.0 { 1 } else { 0 };
+}
+
+fn dolphin_slash(frames: &[Frame], frame_idx: usize, vars: &mut SpecialVars) {
+    if frames[frame_idx].anim_frame >= 6.0 { vars.subaction_flags[3] = 1; }
+}
+
+fn blazer(frames: &[Frame], frame_idx: usize, vars: &mut SpecialVars) {
+    if frames[frame_idx].anim_frame >= 10.0 { vars.subaction_flags[3] = 1; }
+}
+
+fn float(frames: &[Frame], frame_idx: usize, vars: &mut SpecialVars) {
+    fn is_float(f: &Frame) -> bool {
+        matches!(f.state_num, 341 | 344..=348) // Float, FloatNair..FloatDair
+    }
+
+    // frame 0 has no previous frame, so the float starts there
+    let first_float_frame = frames[..frame_idx].iter()
+        .rposition(|f| !is_float(f))
+        .map_or(0, |i| i + 1);
+
+    let float_frames_left = 150usize.saturating_sub(frame_idx - first_float_frame);
+    vars.char_fighter_var[4..8].copy_from_slice(&(float_frames_left as f32).to_be_bytes());
+}
@@ -0,0 +1,15 @@
+// indexed by external stage id.
+// (x, y) of the right ledge of the main platform. The left ledge is mirrored.
+//
+// Only the tournament legal stages are filled out.
+pub fn ledge(stage_external: u16) -> Option<(f32, f32)> {
+    match stage_external {
+        2  => Some((63.35, 0.62)), // Fountain of Dreams
+        3  => Some((87.75, 0.0 )), // Pokemon Stadium
+        8  => Some((56.0 , -3.5)), // Yoshi's Story
+        28 => Some((77.27, 0.01)), // Dream Land
+        31 => Some((68.4 , 0.0 )), // Battlefield
+        32 => Some((85.57, 0.0 )), // Final Destination
+        _ => None,
+    }
+}
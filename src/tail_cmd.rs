@@ -0,0 +1,83 @@
+use tm_replay::*;
+
+// Slippi writes each game to a new file, so a directory means its most recently modified slp.
+fn latest_slp(dir: &std::path::Path) -> Result<std::path::PathBuf, String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Error: Could not read directory '{}': {}", dir.display(), e))?;
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("slp")))
+        .filter_map(|path| Some((std::fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+        .ok_or_else(|| format!("Error: No slp files in '{}'", dir.display()))
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut num_frames = 600;
+    let mut human = HumanPort::HumanLowPort;
+    let mut name = String::from("tail");
+    let mut output_dir = String::from(".");
+    let mut file = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--seconds" => num_frames = crate::parse_num(args, &mut i)? * 60,
+            "-n" | "--num-frames" => num_frames = crate::parse_num(args, &mut i)?,
+            "--human" => {
+                let spec = crate::parse_str(args, &mut i)?;
+                human = crate::batch::parse_human(&spec).map_err(|e| format!("Error: {}", e))?;
+            }
+            "-m" | "--name" => name = crate::parse_str(args, &mut i)?,
+            "-o" | "--gci-dir" => output_dir = crate::parse_str(args, &mut i)?,
+            "-h" | "--help" => {
+                print!("{}", crate::USAGE);
+                return Ok(());
+            }
+            f if file.is_none() && !f.starts_with('-') => {
+                file = Some(f.to_string());
+                i += 1;
+            }
+            err => return Err(format!("Error: Unknown argument '{}'", err)),
+        }
+    }
+
+    let file = file.ok_or("Error: 'tail' requires an slp file or directory")?;
+    let mut path = std::path::PathBuf::from(&file);
+    if path.is_dir() { path = latest_slp(&path)?; }
+
+    let bytes = std::fs::read(&path)
+        .map_err(|e| format!("Error: Could not read file '{}': {}", path.display(), e))?;
+
+    // The metadata with the start time is only written when the game ends.
+    // Slippi creates the file when the game starts, so use that instead.
+    let start_time = std::fs::metadata(&path)
+        .and_then(|m| m.created().or_else(|_| m.modified()))
+        .map_or_else(|_| RecordingTime::today_approx(), RecordingTime::from_system_time);
+    let slp = partial::complete_partial_slp(&bytes, start_time)
+        .ok_or_else(|| format!("Error: '{}' is not an slp file, or the game has not started", path.display()))?;
+
+    // slp_parser::read_game takes a path, so the completed slp is written out first.
+    let tmp_path = std::env::temp_dir().join(format!("tm_replay_tail_{}.slp", std::process::id()));
+    std::fs::write(&tmp_path, &slp)
+        .map_err(|e| format!("Error: Could not write temporary file '{}': {}", tmp_path.display(), e))?;
+    let game = slp_parser::read_game(&tmp_path);
+    let _ = std::fs::remove_file(&tmp_path);
+    let game = game.map_err(|e| format!("Error: failed to parse slp file: {}", e))?;
+
+    let start_frame = partial::tail_start_frame(&game, num_frames);
+    let (savestate, export_report) = construct_tm_replay_from_slp(&game, human, start_frame, num_frames, &name, 0)
+        .map_err(crate::error_message)?;
+
+    let output_dir = std::path::Path::new(&output_dir);
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Error: Could not create directory '{}': {}", output_dir.display(), e))?;
+    let output_file = crate::batch::write_gci(output_dir, start_time, &savestate)?;
+    println!("Savestate file '{}' created from '{}'", output_file, path.display());
+    crate::report::print(&export_report);
+
+    Ok(())
+}